@@ -23,7 +23,9 @@ export default function DeleteServerModal({
       <Modal.Body>
         Do you really want to delete {server?.description}?
         <br />
-        You could always re-add it later.
+        {server?.managed_by
+          ? "It comes from a server directory, so it will stay hidden until you restore hidden servers in Settings."
+          : "You could always re-add it later."}
       </Modal.Body>
       <Modal.Footer>
         <Button
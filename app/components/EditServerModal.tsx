@@ -39,12 +39,37 @@ const validatePort = (port: string) => {
   return true;
 };
 
+const validateIpv6 = (ip: string) => {
+  // loose check; the backend does the real parsing
+  const re = /^[0-9a-fA-F:.]+$/;
+  return ip.includes(":") && re.test(ip);
+};
+
 const validateAddress = (address: string) => {
   const addressTrimmed = address.trim();
   if (addressTrimmed == "") {
     return true; // fine; we have a default
   }
 
+  // bracketed IPv6 literal with optional port, e.g. [2001:db8::1]:23000
+  if (addressTrimmed.startsWith("[")) {
+    const bracket_index = addressTrimmed.indexOf("]");
+    if (bracket_index == -1) {
+      return false;
+    }
+    const ip = addressTrimmed.substring(1, bracket_index);
+    const rest = addressTrimmed.substring(bracket_index + 1);
+    if (rest == "") {
+      return validateIpv6(ip);
+    }
+    return validateIpv6(ip) && rest.startsWith(":") && validatePort(rest.substring(1));
+  }
+
+  // bare IPv6 literal; no port possible without brackets
+  if (addressTrimmed.indexOf(":") != addressTrimmed.lastIndexOf(":")) {
+    return validateIpv6(addressTrimmed);
+  }
+
   // check for port colon
   const colon_index = addressTrimmed.indexOf(":");
   if (colon_index == -1) {
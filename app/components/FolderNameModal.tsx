@@ -0,0 +1,72 @@
+import { Form, Modal } from "react-bootstrap";
+import Button from "./Button";
+import { useState, useEffect } from "react";
+
+export default function FolderNameModal({
+  show,
+  setShow,
+  initialName,
+  onSubmit,
+}: {
+  show: boolean;
+  setShow: (show: boolean) => void;
+  initialName?: string;
+  onSubmit: (name: string) => void;
+}) {
+  const [name, setName] = useState<string>("");
+
+  useEffect(() => {
+    setName(initialName ?? "");
+  }, [show]);
+
+  const validate = () => {
+    return name.trim().length > 0;
+  };
+
+  const onHitSubmit = () => {
+    if (validate()) {
+      onSubmit(name.trim());
+      setShow(false);
+    }
+  };
+
+  return (
+    <Modal show={show} onHide={() => setShow(false)} centered>
+      <Modal.Header closeButton>
+        <Modal.Title>{initialName ? "Rename Folder" : "New Folder"}</Modal.Title>
+      </Modal.Header>
+      <Modal.Body className="p-0">
+        <Form
+          className="p-3"
+          onSubmit={(e) => {
+            e.preventDefault();
+            onHitSubmit();
+          }}
+        >
+          <Form.Group controlId="folderName">
+            <Form.Control
+              type="text"
+              value={name}
+              onChange={(e) => setName(e.target.value)}
+              placeholder="Folder name"
+              autoFocus
+            />
+          </Form.Group>
+        </Form>
+      </Modal.Body>
+      <Modal.Footer>
+        <Button
+          variant="primary"
+          onClick={() => setShow(false)}
+          text="Cancel"
+        />
+        <Button
+          variant="success"
+          text={initialName ? "Rename" : "Create"}
+          enabled={validate()}
+          onClick={() => onHitSubmit()}
+        />
+      </Modal.Footer>
+    </Modal>
+  );
+}
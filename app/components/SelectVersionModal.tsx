@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "@/app/util";
 import Modal from "react-bootstrap/Modal";
 import Form from "react-bootstrap/Form";
 
@@ -61,7 +62,9 @@ export default function SelectVersionModal({
         const availableVersions = await getVersionsForServer(server!);
         setAvailable(availableVersions);
       } catch (e: unknown) {
-        console.error("Failed to fetch available versions: " + e);
+        console.error(
+          "Failed to fetch available versions: " + getErrorMessage(e)
+        );
         setAvailable([]);
       }
     };
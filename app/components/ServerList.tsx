@@ -1,6 +1,13 @@
-import { ServerEntry, VersionEntry } from "@/app/types";
+import {
+  ServerEntry,
+  ServerFolder,
+  ServerStatus,
+  VersionEntry,
+} from "@/app/types";
 import { useEffect, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import FolderNameModal from "./FolderNameModal";
 
 const findVersion = (versions: VersionEntry[], uuid: string) => {
   return versions.find((version) => version.uuid == uuid);
@@ -18,74 +25,59 @@ const getVersionsForServer = async (server: ServerEntry) => {
   return versions;
 };
 
-const getPlayerCountForServer = async (server: ServerEntry) => {
-  if (!server.endpoint) {
-    // Not available for simple servers
-    return undefined;
+const getStatusTooltip = (status: ServerStatus) => {
+  let tooltip = status.latency_ms + " ms";
+  if (status.api_version) {
+    tooltip += ", API v" + status.api_version;
   }
-
-  // Call the backend
-  const count: number = await invoke("get_player_count_for_server", {
-    uuid: server.uuid,
-  });
-  return count;
+  return tooltip;
 };
 
-function PlayerCount({ server, refreshes }: { server: ServerEntry, refreshes: number }) {
-  const [playerCount, setPlayerCount] = useState<number | undefined>(undefined);
-  const [error, setError] = useState<boolean>(false);
-
-  useEffect(() => {
-    const fetchPlayerCount = async () => {
-      try {
-        const count = await getPlayerCountForServer(server);
-        setPlayerCount(count);
-        setError(false);
-      } catch (e) {
-        console.warn(e);
-        setError(true);
-      }
-    };
-
-    setError(false);
-    setPlayerCount(undefined);
-    fetchPlayerCount();
-  }, [server, refreshes]);
+function PlayerCount({
+  server,
+  status,
+}: {
+  server: ServerEntry;
+  status?: ServerStatus;
+}) {
+  if (!status) {
+    return (
+      <span
+        className="spinner-border spinner-border-sm"
+        role="status"
+        aria-hidden="true"
+      ></span>
+    );
+  }
 
-  if (playerCount !== undefined) {
+  if (!status.online) {
     return (
-      <span className="fw-bold text-success" title="Current player count">
-        <i className="fa fa-user fa-sm"></i> {playerCount}
+      <span className="fw-bold text-danger">
+        <i
+          className="fa fa-plug-circle-xmark"
+          title="Could not connect to server"
+        ></i>
       </span>
     );
   }
 
-  if (server.endpoint) {
-    if (error) {
-      return (
-        <span className="fw-bold text-danger">
-          <i
-            className="fa fa-plug-circle-xmark"
-            title="Could not connect to server"
-          ></i>
-        </span>
-      );
-    }
+  if (server.endpoint && status.player_count !== undefined) {
     return (
       <span
-        className="spinner-border spinner-border-sm"
-        role="status"
-        aria-hidden="true"
-      ></span>
+        className="fw-bold text-success"
+        title={"Current player count (" + getStatusTooltip(status) + ")"}
+      >
+        <i className="fa fa-user fa-sm"></i> {status.player_count}
+      </span>
     );
   }
 
   return (
-    <span>
-      <i
-        className="fa-solid fa-circle-question"
-        title="No information provided"
-      ></i>
+    <span
+      className="fw-bold text-success"
+      title={"Server reachable (" + getStatusTooltip(status) + ")"}
+    >
+      <i className="fa fa-signal fa-sm"></i>
     </span>
   );
 }
@@ -168,23 +160,192 @@ function VersionBadges({
   }
 }
 
+type DragItem = {
+  kind: "server" | "folder";
+  uuid: string;
+};
+
 export default function ServerList({
   servers,
+  favorites,
+  folders,
   versions,
   selectedServer,
   setSelectedServer,
   onConnect,
   reloadVersions,
+  onToggleFavorite,
+  onMoveServer,
+  onMoveFolder,
+  onAddFolder,
+  onRenameFolder,
+  onDeleteFolder,
 }: {
   servers?: ServerEntry[];
+  favorites: string[];
+  folders: ServerFolder[];
   versions: VersionEntry[];
   selectedServer?: string;
   setSelectedServer: (server: string) => void;
   onConnect: (server: string) => void;
   reloadVersions: () => Promise<void>;
+  onToggleFavorite: (server: string) => void;
+  onMoveServer: (server: string, folder?: string, before?: string) => void;
+  onMoveFolder: (folder: string, before?: string) => void;
+  onAddFolder: (name: string) => void;
+  onRenameFolder: (folder: string, name: string) => void;
+  onDeleteFolder: (folder: string) => void;
 }) {
-  const [statusRefreshes, setStatusRefreshes] = useState(0);
+  const [statuses, setStatuses] = useState<Record<string, ServerStatus>>({});
   const [supportedVersionRefreshes, setSupportedVersionRefreshes] = useState(0);
+  const [dragItem, setDragItem] = useState<DragItem | undefined>(undefined);
+  const [showFolderModal, setShowFolderModal] = useState(false);
+  const [editingFolder, setEditingFolder] = useState<ServerFolder | undefined>(
+    undefined
+  );
+
+  const refreshStatuses = async () => {
+    setStatuses({});
+    await invoke("refresh_server_statuses");
+    const cached: Record<string, ServerStatus> = await invoke(
+      "get_server_statuses",
+    );
+    setStatuses(cached);
+  };
+
+  useEffect(() => {
+    const listener = listen<ServerStatus>("server_status", (e) => {
+      setStatuses((statuses) => ({ ...statuses, [e.payload.uuid]: e.payload }));
+    });
+    invoke("get_server_statuses").then((cached) => {
+      setStatuses((statuses) => ({
+        ...(cached as Record<string, ServerStatus>),
+        ...statuses,
+      }));
+    });
+
+    return () => {
+      listener.then((unlisten) => unlisten());
+    };
+  }, []);
+
+  const getFolder = (server: ServerEntry) => {
+    return folders.find((folder) => folder.uuid == server.folder);
+  };
+
+  // servers come from the backend already in display order
+  const ungrouped = (servers ?? []).filter((server) => !getFolder(server));
+  const groups = folders.map((folder) => ({
+    folder,
+    servers: (servers ?? []).filter((server) => server.folder == folder.uuid),
+  }));
+
+  const onDropOnServer = (target: ServerEntry) => {
+    if (dragItem?.kind == "server" && dragItem.uuid != target.uuid) {
+      onMoveServer(dragItem.uuid, getFolder(target)?.uuid, target.uuid);
+    }
+    setDragItem(undefined);
+  };
+
+  const onDropOnFolder = (target?: ServerFolder) => {
+    if (dragItem?.kind == "server") {
+      // to the end of the folder, or of the ungrouped servers
+      onMoveServer(dragItem.uuid, target?.uuid);
+    } else if (dragItem?.kind == "folder" && dragItem.uuid != target?.uuid) {
+      onMoveFolder(dragItem.uuid, target?.uuid);
+    }
+    setDragItem(undefined);
+  };
+
+  const openFolderModal = (folder?: ServerFolder) => {
+    setEditingFolder(folder);
+    setShowFolderModal(true);
+  };
+
+  const renderServer = (server: ServerEntry) => {
+    const favorite = favorites.includes(server.uuid);
+    return (
+      <tr
+        key={server.uuid}
+        className={
+          "server-listing-entry " +
+          (selectedServer == server.uuid ? "table-active" : "")
+        }
+        draggable
+        onDragStart={() => setDragItem({ kind: "server", uuid: server.uuid })}
+        onDragEnd={() => setDragItem(undefined)}
+        onDragOver={(e) => e.preventDefault()}
+        onDrop={() => onDropOnServer(server)}
+        onClick={() => setSelectedServer(server.uuid!)}
+        onDoubleClick={() => onConnect(server.uuid!)}
+      >
+        <td className="text-start name-column">
+          <i
+            className={
+              (favorite ? "fa" : "far") +
+              " fa-star me-2 clickable" +
+              (favorite ? " text-warning" : "")
+            }
+            title={favorite ? "Unfavorite" : "Favorite"}
+            onClick={(e) => {
+              e.stopPropagation();
+              onToggleFavorite(server.uuid);
+            }}
+          ></i>
+          {server.description}
+          {server.managed_by && (
+            <i
+              className="fa fa-globe ms-2 text-secondary"
+              title="From a server directory"
+            ></i>
+          )}
+        </td>
+        <td className="font-monospace versions-column">
+          <VersionBadges
+            server={server}
+            versions={versions}
+            reloadVersions={reloadVersions}
+            refreshes={supportedVersionRefreshes}
+          />
+        </td>
+        <td className="font-monospace text-end status-column">
+          <PlayerCount server={server} status={statuses[server.uuid!]} />
+        </td>
+      </tr>
+    );
+  };
+
+  const renderFolder = (folder: ServerFolder, folderServers: ServerEntry[]) => {
+    return [
+      <tr
+        key={folder.uuid}
+        className="server-folder-header"
+        draggable
+        onDragStart={() => setDragItem({ kind: "folder", uuid: folder.uuid })}
+        onDragEnd={() => setDragItem(undefined)}
+        onDragOver={(e) => e.preventDefault()}
+        onDrop={() => onDropOnFolder(folder)}
+      >
+        <td className="text-start fw-bold" colSpan={2}>
+          <i className="fa fa-folder me-2"></i>
+          {folder.name}
+        </td>
+        <td className="text-end status-column">
+          <i
+            className="fa fa-pen me-2 clickable"
+            title="Rename folder"
+            onClick={() => openFolderModal(folder)}
+          ></i>
+          <i
+            className="fa fa-trash clickable"
+            title="Delete folder (servers in it are kept)"
+            onClick={() => onDeleteFolder(folder.uuid)}
+          ></i>
+        </td>
+      </tr>,
+      ...folderServers.map(renderServer),
+    ];
+  };
 
   return (
     <div
@@ -193,15 +354,25 @@ export default function ServerList({
     >
       <table className="table table-striped table-hover mb-0">
         <thead>
-          <tr>
-            <th className="text-start name-column">Server Name</th>
+          <tr
+            onDragOver={(e) => e.preventDefault()}
+            onDrop={() => onDropOnFolder(undefined)}
+          >
+            <th className="text-start name-column">
+              Server Name
+              <i
+                onClick={() => openFolderModal(undefined)}
+                className="fa fa-folder-plus ms-2 clickable"
+                title="New folder"
+              ></i>
+            </th>
             <th className="versions-column">
               Game Versions
               <i onClick={() => setSupportedVersionRefreshes((r) => r + 1)} className="fa fa-rotate-right ms-2 clickable"></i>
             </th>
             <th className="text-end status-column">
               Status
-              <i onClick={() => setStatusRefreshes((r) => r + 1)} className="fa fa-rotate-right ms-2 clickable"></i>
+              <i onClick={refreshStatuses} className="fa fa-rotate-right ms-2 clickable"></i>
             </th>
           </tr>
         </thead>
@@ -216,38 +387,30 @@ export default function ServerList({
                 ></span>
               </td>
             </tr>
-          ) : servers.length === 0 ? (
+          ) : servers.length === 0 && folders.length === 0 ? (
             <tr>
               <td colSpan={3}>No servers available.</td>
             </tr>
           ) : (
-            servers.map((server) => (
-              <tr
-                key={server.uuid}
-                className={
-                  "server-listing-entry " +
-                  (selectedServer == server.uuid ? "table-active" : "")
-                }
-                onClick={() => setSelectedServer(server.uuid!)}
-                onDoubleClick={() => onConnect(server.uuid!)}
-              >
-                <td className="text-start name-column">{server.description}</td>
-                <td className="font-monospace versions-column">
-                  <VersionBadges
-                    server={server}
-                    versions={versions}
-                    reloadVersions={reloadVersions}
-                    refreshes={supportedVersionRefreshes}
-                  />
-                </td>
-                <td className="font-monospace text-end status-column">
-                  <PlayerCount server={server} refreshes={statusRefreshes} />
-                </td>
-              </tr>
-            ))
+            <>
+              {ungrouped.map(renderServer)}
+              {groups.map((group) => renderFolder(group.folder, group.servers))}
+            </>
           )}
         </tbody>
       </table>
+      <FolderNameModal
+        show={showFolderModal}
+        setShow={setShowFolderModal}
+        initialName={editingFolder?.name}
+        onSubmit={(name) => {
+          if (editingFolder) {
+            onRenameFolder(editingFolder.uuid, name);
+          } else {
+            onAddFolder(name);
+          }
+        }}
+      />
     </div>
   );
 }
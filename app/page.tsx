@@ -13,6 +13,7 @@ import Stack from "react-bootstrap/Stack";
 
 import {
   ServerEntry,
+  ServerFolder,
   NewServerDetails,
   Servers,
   Alert,
@@ -39,11 +40,14 @@ import LogoImages from "@/components/LogoImages";
 import SelectVersionModal from "@/components/SelectVersionModal";
 import Toasts from "@/components/Toasts";
 import { listen } from "@tauri-apps/api/event";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import {
   getDebugMode,
   getTheme,
   getUseCustomTitlebar,
   sleep,
+  getErrorMessage,
+  isLauncherError,
 } from "@/app/util";
 import ForgotPasswordModal from "./components/ForgotPasswordModal";
 import ConfirmationModal from "./components/ConfirmationModal";
@@ -67,6 +71,8 @@ export default function Home() {
   const [initialFetchDone, setInitialFetchDone] = useState(false);
   const [config, setConfig] = useState<Config | undefined>(undefined);
   const [servers, setServers] = useState<ServerEntry[]>([]);
+  const [favorites, setFavorites] = useState<string[]>([]);
+  const [folders, setFolders] = useState<ServerFolder[]>([]);
   const [versions, setVersions] = useState<VersionEntry[]>([]);
 
   const [selectedIdx, setSelectedIdx] = useState<number>(-1);
@@ -169,6 +175,8 @@ export default function Home() {
   const syncServers = async () => {
     const serverData: Servers = await invoke("get_servers");
     setServers(serverData.servers);
+    setFavorites(serverData.favorites);
+    setFolders(serverData.folders);
   };
 
   const syncVersions = async () => {
@@ -199,7 +207,7 @@ export default function Home() {
         alertInfo("Update available: " + updateInfo.version, updateInfo.url);
       }
     } catch (e: unknown) {
-      console.warn("Failed to check for updates (" + e + ")");
+      console.warn("Failed to check for updates (" + getErrorMessage(e) + ")");
     }
   };
 
@@ -217,6 +225,24 @@ export default function Home() {
     setInitialFetchDone(true);
   };
 
+  const describeImportCounts = (counts: ImportCounts) => {
+    let text = "Imported ";
+    if (counts.version_count > 0) {
+      text +=
+        counts.version_count +
+        (counts.version_count > 1 ? " versions " : " version ");
+      if (counts.server_count > 0) {
+        text += "and ";
+      }
+    }
+    if (counts.server_count > 0) {
+      text +=
+        counts.server_count +
+        (counts.server_count > 1 ? " servers " : " server ");
+    }
+    return text;
+  };
+
   const importFromOpenFusionClient = async () => {
     startLoading("import", "Importing");
     try {
@@ -224,25 +250,12 @@ export default function Home() {
       if (counts.server_count == 0 && counts.version_count == 0) {
         console.log("Nothing to import");
       } else {
-        let text = "Imported ";
-        if (counts.version_count > 0) {
-          text +=
-            counts.version_count +
-            (counts.version_count > 1 ? " versions " : " version ");
-          if (counts.server_count > 0) {
-            text += "and ";
-          }
-        }
-        if (counts.server_count > 0) {
-          text +=
-            counts.server_count +
-            (counts.server_count > 1 ? " servers " : " server ");
-        }
-        text += "from OpenFusionClient";
-        alertSuccess(text);
+        alertSuccess(describeImportCounts(counts) + "from OpenFusionClient");
       }
     } catch (e: unknown) {
-      alertError("Failed to import from OpenFusionClient (" + e + ")");
+      alertError(
+        "Failed to import from OpenFusionClient (" + getErrorMessage(e) + ")"
+      );
     }
     stopLoading("import");
   };
@@ -259,7 +272,7 @@ export default function Home() {
     try {
       await updateServer(details, serverUuid, false);
     } catch (e: unknown) {
-      console.warn("Failed to set version for server: " + e);
+      console.warn("Failed to set version for server: " + getErrorMessage(e));
     }
     onConnect(serverUuid, versionUuid);
   };
@@ -296,7 +309,7 @@ export default function Home() {
       await getCurrentWindow().setFocus();
     } catch (e: unknown) {
       await getCurrentWindow().show();
-      alertError("Error during init (" + e + ")");
+      alertError("Error during init (" + getErrorMessage(e) + ")");
     }
   };
 
@@ -328,7 +341,7 @@ export default function Home() {
       }
     } catch (e: unknown) {
       await getCurrentWindow().show();
-      alertError("Failed to launch (" + e + ")");
+      alertError("Failed to launch (" + getErrorMessage(e) + ")");
     }
     stopLoading("launch");
   };
@@ -355,7 +368,7 @@ export default function Home() {
         alertInfo(res.resp);
       }
     } catch (e: unknown) {
-      alertError("Failed to register (" + e + ")");
+      alertError("Failed to register (" + getErrorMessage(e) + ")");
     }
     stopLoading("do_register");
   };
@@ -375,7 +388,7 @@ export default function Home() {
         remember: remember,
       });
     } catch (e: unknown) {
-      alertError("Failed to login (" + e + ")");
+      alertError("Failed to login (" + getErrorMessage(e) + ")");
       return;
     } finally {
       stopLoading("do_login");
@@ -404,9 +417,12 @@ export default function Home() {
           });
           session = loginSession;
           setCurrentSession(loginSession);
-        } catch {
+        } catch (e: unknown) {
           // If we can't get a session token for ANY REASON, we'll grab a new refresh token
           // by making the user log in again
+          if (isLauncherError(e) && e.kind == "session_expired") {
+            alertInfo(e.message);
+          }
           stopLoading("configure_endpoint");
           setShowLoginModal(true);
           setConnecting(false);
@@ -422,7 +438,7 @@ export default function Home() {
         });
       } catch (e: unknown) {
         stopLoading("configure_endpoint");
-        alertError("Failed to get versions: " + e);
+        alertError("Failed to get versions: " + getErrorMessage(e));
         setConnecting(false);
         return;
       }
@@ -457,14 +473,11 @@ export default function Home() {
   const addServer = async (details: NewServerDetails) => {
     try {
       const uuid: string = await invoke("add_server", { details: details });
-      setServers((servers) => {
-        const newServer: ServerEntry = { ...details, uuid };
-        return [...servers, newServer];
-      });
+      await syncServers();
       setSelectedServer(uuid);
       alertSuccess("Server added");
     } catch (e: unknown) {
-      alertError("Failed to add server (" + e + ")");
+      alertError("Failed to add server (" + getErrorMessage(e) + ")");
     }
     stopLoading("add_server");
   };
@@ -480,7 +493,7 @@ export default function Home() {
       setServers((servers) => {
         const newServers = servers.map((server) => {
           if (server.uuid == uuid) {
-            return entry;
+            return { ...entry, folder: server.folder };
           }
           return server;
         });
@@ -490,7 +503,7 @@ export default function Home() {
         alertSuccess("Server updated");
       }
     } catch (e: unknown) {
-      alertError("Failed to update server (" + e + ")");
+      alertError("Failed to update server (" + getErrorMessage(e) + ")");
     }
     stopLoading("update_server");
   };
@@ -517,11 +530,131 @@ export default function Home() {
         }
         alertSuccess("Server deleted");
       } catch (e: unknown) {
-        alertError("Failed to delete server (" + e + ")");
+        alertError("Failed to delete server (" + getErrorMessage(e) + ")");
       }
     }
   };
 
+  const exportServers = async () => {
+    // just the selected server, or the whole list if nothing is selected
+    const selected = getSelectedServer();
+    const toExport = selected ? [selected] : servers;
+    const path = await save({
+      defaultPath: "servers.json",
+      filters: [
+        {
+          name: "Server list",
+          extensions: ["json"],
+        },
+      ],
+    });
+    if (!path) {
+      return;
+    }
+    try {
+      await invoke("export_servers", {
+        uuids: toExport.map((s) => s.uuid),
+        // endpoint servers get their versions from the endpoint
+        includeVersions: toExport.some((s) => !s.endpoint),
+        path,
+      });
+      alertSuccess(
+        "Exported " +
+          toExport.length +
+          (toExport.length > 1 ? " servers" : " server")
+      );
+    } catch (e: unknown) {
+      alertError("Failed to export servers (" + getErrorMessage(e) + ")");
+    }
+  };
+
+  const importServers = async () => {
+    const path = await open({
+      multiple: false,
+      directory: false,
+      filters: [
+        {
+          name: "Server list",
+          extensions: ["json"],
+        },
+      ],
+    });
+    if (!path) {
+      return;
+    }
+    try {
+      const counts: ImportCounts = await invoke("import_servers", { path });
+      if (counts.server_count == 0 && counts.version_count == 0) {
+        alertInfo("All of these servers are already in your list");
+      } else {
+        alertSuccess(describeImportCounts(counts).trim());
+        await syncServersAndVersions();
+      }
+    } catch (e: unknown) {
+      alertError("Failed to import servers (" + getErrorMessage(e) + ")");
+    }
+  };
+
+  const toggleFavorite = async (serverUuid: string) => {
+    try {
+      await invoke("set_server_favorite", {
+        uuid: serverUuid,
+        favorite: !favorites.includes(serverUuid),
+      });
+    } catch (e: unknown) {
+      alertError("Failed to favorite server (" + getErrorMessage(e) + ")");
+    }
+    await syncServers();
+  };
+
+  const moveServer = async (
+    serverUuid: string,
+    folderUuid?: string,
+    beforeUuid?: string
+  ) => {
+    try {
+      await invoke("move_server", {
+        uuid: serverUuid,
+        folder: folderUuid,
+        before: beforeUuid,
+      });
+    } catch (e: unknown) {
+      alertError("Failed to move server (" + getErrorMessage(e) + ")");
+    }
+    await syncServers();
+  };
+
+  const moveFolder = async (folderUuid: string, beforeUuid?: string) => {
+    try {
+      await invoke("move_server_folder", {
+        uuid: folderUuid,
+        before: beforeUuid,
+      });
+    } catch (e: unknown) {
+      alertError("Failed to move folder (" + getErrorMessage(e) + ")");
+    }
+    await syncServers();
+  };
+
+  const addFolder = async (name: string) => {
+    await invoke("add_server_folder", { name });
+    await syncServers();
+  };
+
+  const renameFolder = async (folderUuid: string, name: string) => {
+    try {
+      await invoke("rename_server_folder", { uuid: folderUuid, name });
+    } catch (e: unknown) {
+      alertError("Failed to rename folder (" + getErrorMessage(e) + ")");
+    }
+    await syncServers();
+  };
+
+  const deleteFolder = async (folderUuid: string) => {
+    await invoke("delete_server_folder", { uuid: folderUuid });
+    await syncServers();
+  };
+
   const sendOneTimePassword = async (email: string) => {
     try {
       await invoke("send_otp", {
@@ -531,7 +664,9 @@ export default function Home() {
       setShowForgotPasswordModal(false);
       alertSuccess("One-time password sent");
     } catch (e: unknown) {
-      alertError("Failed to send one-time password (" + e + ")");
+      alertError(
+        "Failed to send one-time password (" + getErrorMessage(e) + ")"
+      );
     }
   };
 
@@ -549,6 +684,25 @@ export default function Home() {
       listen<AlertEvent>("alert", (e) => {
         handleAlert(e.payload);
       });
+      // e.g. servers from a server directory
+      listen<string>("servers_changed", () => {
+        syncServers();
+      });
+      // from an openfusion:// link, which anyone could have sent
+      listen<NewServerDetails>("add_server_requested", (e) => {
+        const details = e.payload;
+        showConfirmationModal(
+          "Add **" +
+            details.description +
+            "** (`" +
+            details.endpoint +
+            "`) to your server list?",
+          "Add",
+          "success",
+          () => addServer(details),
+          "Add server"
+        );
+      });
       loadedRef.current = true;
     }
   }, []);
@@ -608,6 +762,8 @@ export default function Home() {
           <Col xs={8} className="mb-2 main-col">
             <ServerList
               servers={servers}
+              favorites={favorites}
+              folders={folders}
               versions={versions}
               selectedServer={getSelectedServer()?.uuid}
               setSelectedServer={setSelectedServer}
@@ -617,6 +773,12 @@ export default function Home() {
                 setConnecting(true);
                 onConnect(serverUuid);
               }}
+              onToggleFavorite={toggleFavorite}
+              onMoveServer={moveServer}
+              onMoveFolder={moveFolder}
+              onAddFolder={addFolder}
+              onRenameFolder={renameFolder}
+              onDeleteFolder={deleteFolder}
             />
           </Col>
         </Row>
@@ -631,7 +793,11 @@ export default function Home() {
               />
               <Button
                 onClick={() => setShowEditModal(true)}
-                enabled={getSelectedServer() ? true : false}
+                enabled={
+                  getSelectedServer() && !getSelectedServer()!.managed_by
+                    ? true
+                    : false
+                }
                 variant="primary"
                 icon="edit"
                 tooltip="Edit server"
@@ -643,6 +809,21 @@ export default function Home() {
                 icon="trash"
                 tooltip="Delete server"
               />
+              <Button
+                onClick={() => importServers()}
+                variant="primary"
+                icon="file-import"
+                tooltip="Import servers"
+              />
+              <Button
+                onClick={() => exportServers()}
+                enabled={servers.length > 0}
+                variant="primary"
+                icon="file-export"
+                tooltip={
+                  getSelectedServer() ? "Export server" : "Export all servers"
+                }
+              />
             </Stack>
           </Col>
           <Col xs={4} className="side-col">
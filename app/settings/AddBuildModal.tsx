@@ -16,7 +16,7 @@ export default function AddBuildModal({
   show: boolean;
   setShow: (show: boolean) => void;
   onImport: (manifestPath: string) => Promise<boolean>;
-  onManualAdd: (name: string, assetUrl: string) => void;
+  onManualAdd: (name: string, assetUrl: string, mirrors: string[]) => void;
 }) {
   const [tab, setTab] = useState(TAB_IMPORT);
 
@@ -29,6 +29,7 @@ export default function AddBuildModal({
   // Manual tab
   const [name, setName] = useState<string>("");
   const [assetUrl, setAssetUrl] = useState<string>("");
+  const [mirrors, setMirrors] = useState<string>("");
   const validateManual = () => {
     return assetUrl.trim() != "";
   };
@@ -39,6 +40,7 @@ export default function AddBuildModal({
       setManifestPath("");
       setName("");
       setAssetUrl("");
+      setMirrors("");
     }
   }, [show]);
 
@@ -60,7 +62,11 @@ export default function AddBuildModal({
 
   const onSubmit = async () => {
     if (tab == TAB_MANUAL) {
-      onManualAdd(name, assetUrl);
+      const mirrorList = mirrors
+        .split("\n")
+        .map((m) => m.trim())
+        .filter((m) => m != "");
+      onManualAdd(name, assetUrl, mirrorList);
       setShow(false);
     } else {
       const succeeded = await onImport(manifestPath!);
@@ -117,6 +123,19 @@ export default function AddBuildModal({
                   placeholder="https://cdn.example.com/build"
                 />
               </Form.Group>
+              <Form.Group className="mb-3" controlId="editMirrors">
+                <Form.Label>Mirrors (optional, one per line)</Form.Label>
+                <Form.Control
+                  as="textarea"
+                  rows={3}
+                  value={mirrors}
+                  onChange={(e) => setMirrors(e.target.value)}
+                  placeholder="https://mirror.example.com/build"
+                />
+                <Form.Text className="text-muted">
+                  Used in order when the asset URL is down
+                </Form.Text>
+              </Form.Group>
             </Form>
           </Tab>
         </Tabs>
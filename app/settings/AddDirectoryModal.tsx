@@ -0,0 +1,93 @@
+import { Form, Modal } from "react-bootstrap";
+import Button from "@/components/Button";
+import { useState, useEffect } from "react";
+
+export default function AddDirectoryModal({
+  show,
+  setShow,
+  onSubmit,
+}: {
+  show: boolean;
+  setShow: (show: boolean) => void;
+  onSubmit: (url: string, publicKey: string) => Promise<boolean>;
+}) {
+  const [url, setUrl] = useState<string>("");
+  const [publicKey, setPublicKey] = useState<string>("");
+  const [loading, setLoading] = useState<boolean>(false);
+
+  useEffect(() => {
+    setUrl("");
+    setPublicKey("");
+  }, [show]);
+
+  const validate = () => {
+    return url.trim().startsWith("https://") && publicKey.trim().length > 0;
+  };
+
+  const onHitSubmit = async () => {
+    if (!validate()) {
+      return;
+    }
+    setLoading(true);
+    const succeeded = await onSubmit(url.trim(), publicKey.trim());
+    setLoading(false);
+    if (succeeded) {
+      setShow(false);
+    }
+  };
+
+  return (
+    <Modal show={show} onHide={() => setShow(false)} centered>
+      <Modal.Header closeButton>
+        <Modal.Title>Add Server Directory</Modal.Title>
+      </Modal.Header>
+      <Modal.Body className="p-0">
+        <p className="px-3 pt-3 mb-0">
+          Servers from the directory are added to your server list and kept up
+          to date. The list must be signed with the key you enter here.
+        </p>
+        <Form
+          className="p-3"
+          onSubmit={(e) => {
+            e.preventDefault();
+            onHitSubmit();
+          }}
+        >
+          <Form.Group className="mb-3" controlId="directoryUrl">
+            <Form.Label>Directory URL</Form.Label>
+            <Form.Control
+              type="text"
+              value={url}
+              onChange={(e) => setUrl(e.target.value)}
+              placeholder="https://example.com/servers.json"
+            />
+          </Form.Group>
+          <Form.Group controlId="directoryPublicKey">
+            <Form.Label>Public key</Form.Label>
+            <Form.Control
+              type="text"
+              className="font-monospace"
+              value={publicKey}
+              onChange={(e) => setPublicKey(e.target.value)}
+              placeholder="Base64 ed25519 public key"
+            />
+          </Form.Group>
+        </Form>
+      </Modal.Body>
+      <Modal.Footer>
+        <Button
+          variant="primary"
+          onClick={() => setShow(false)}
+          text="Cancel"
+        />
+        <Button
+          variant="success"
+          text="Subscribe"
+          loading={loading}
+          enabled={validate()}
+          onClick={() => onHitSubmit()}
+        />
+      </Modal.Footer>
+    </Modal>
+  );
+}
@@ -1,13 +1,20 @@
 import Button from "../components/Button";
-import { LoginSession, RegistrationResult, ServerEntry } from "@/app/types";
+import {
+  LoginSession,
+  RegistrationResult,
+  SavedAccounts,
+  ServerEntry,
+} from "@/app/types";
 import {
   getBackgroundImageStyle,
   getBackgroundImageUrlForServer,
   getLogoImageUrlForServer,
+  getErrorMessage,
 } from "@/app/util";
 import { invoke } from "@tauri-apps/api/core";
 import { CSSProperties, useContext, useEffect, useState } from "react";
 import { SettingsCtx } from "@/app/contexts";
+import { Form } from "react-bootstrap";
 import LoginModal from "@/components/LoginModal";
 import ForgotPasswordModal from "@/components/ForgotPasswordModal";
 import ManageAccountModal from "./ManageAccountModal";
@@ -25,6 +32,9 @@ function ListEntry({
   const [session, setSession] = useState<LoginSession | undefined | null>(
     undefined,
   );
+  const [accounts, setAccounts] = useState<SavedAccounts | undefined>(
+    undefined,
+  );
 
   const [showLoginModal, setShowLoginModal] = useState(false);
   const [showForgotPasswordModal, setShowForgotPasswordModal] = useState(false);
@@ -52,6 +62,26 @@ function ListEntry({
     } catch (e) {
       setSession(null);
     }
+
+    // fetched after the session so any account renames are picked up
+    const accounts: SavedAccounts = await invoke("get_saved_accounts", {
+      serverUuid: server.uuid,
+    });
+    setAccounts(accounts);
+  };
+
+  const selectAccount = async (username: string) => {
+    try {
+      await invoke("select_account", {
+        serverUuid: server.uuid,
+        username: username,
+      });
+      loadSession();
+    } catch (e: unknown) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to switch account: " + getErrorMessage(e));
+      }
+    }
   };
 
   const logIn = () => {
@@ -61,15 +91,21 @@ function ListEntry({
   const logOut = async () => {
     setButtonLoading(true);
     try {
-      await invoke("do_logout", { serverUuid: server.uuid });
+      await invoke("do_logout", {
+        serverUuid: server.uuid,
+        username: accounts?.selected,
+      });
       if (ctx.alertSuccess) {
-        const txt = "Logged out of " + server.description;
+        const txt =
+          "Logged out of " +
+          (accounts?.selected ? accounts.selected + " on " : "") +
+          server.description;
         ctx.alertSuccess(txt);
       }
       loadSession();
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to log out: " + e);
+        ctx.alertError("Failed to log out: " + getErrorMessage(e));
       }
     }
     setButtonLoading(false);
@@ -90,7 +126,7 @@ function ListEntry({
       loadSession();
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to login: " + e);
+        ctx.alertError("Failed to login: " + getErrorMessage(e));
       }
     }
     setButtonLoading(false);
@@ -122,7 +158,7 @@ function ListEntry({
       }
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to register: " + e);
+        ctx.alertError("Failed to register: " + getErrorMessage(e));
       }
     }
     setButtonLoading(false);
@@ -137,7 +173,9 @@ function ListEntry({
       }
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to send one-time password (" + e + ")");
+        ctx.alertError(
+          "Failed to send one-time password (" + getErrorMessage(e) + ")"
+        );
       }
     }
   };
@@ -151,7 +189,9 @@ function ListEntry({
       setShowManageAccountModal(false);
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to send verification email: " + e);
+        ctx.alertError(
+          "Failed to send verification email: " + getErrorMessage(e)
+        );
       }
     }
   };
@@ -165,7 +205,7 @@ function ListEntry({
       setShowManageAccountModal(false);
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to update password: " + e);
+        ctx.alertError("Failed to update password: " + getErrorMessage(e));
       }
     }
   };
@@ -236,6 +276,28 @@ function ListEntry({
                   <small className="text-muted">logged in as</small>
                   <h4 className="d-inline">{" " + session.username}</h4>
                 </span>
+                {accounts && accounts.usernames.length > 1 && (
+                  <Form.Select
+                    size="sm"
+                    className="mb-2"
+                    value={accounts.selected}
+                    onChange={(e) => selectAccount(e.target.value)}
+                  >
+                    {accounts.usernames.map((username) => (
+                      <option key={username} value={username}>
+                        {username}
+                      </option>
+                    ))}
+                  </Form.Select>
+                )}
+                <Button
+                  loading={buttonLoading}
+                  icon="user-plus"
+                  text="Add Account"
+                  onClick={logIn}
+                  variant="success"
+                  className="me-2"
+                />
                 <Button
                   loading={buttonLoading}
                   icon="user"
@@ -1,14 +1,20 @@
 import { useState, useEffect, useContext } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "@/app/util";
 import { SettingsCtx } from "@/app/contexts";
 import { Stack } from "react-bootstrap";
 import Button from "@/components/Button";
-import { ServerEntry, Servers } from "@/app/types";
+import { ServerEntry, Servers, TokenStoreStatus } from "@/app/types";
 import AuthenticationList from "./AuthenticationList";
+import UnlockTokensModal from "./UnlockTokensModal";
 
 export default function AuthenticationTab({ active }: { active: boolean }) {
   const [servers, setServers] = useState<ServerEntry[] | undefined>(undefined);
   const [refreshes, setRefreshes] = useState(0);
+  const [tokenStore, setTokenStore] = useState<TokenStoreStatus | undefined>(
+    undefined,
+  );
+  const [showUnlockModal, setShowUnlockModal] = useState(false);
 
   const ctx = useContext(SettingsCtx);
 
@@ -17,6 +23,26 @@ export default function AuthenticationTab({ active }: { active: boolean }) {
     setServers(servers.servers);
   };
 
+  const fetchTokenStoreStatus = async () => {
+    const status: TokenStoreStatus = await invoke("get_token_store_status");
+    setTokenStore(status);
+  };
+
+  const unlockTokens = async (passphrase: string) => {
+    try {
+      await invoke("unlock_token_store", { passphrase });
+      setShowUnlockModal(false);
+      if (ctx.alertSuccess) {
+        ctx.alertSuccess("Saved logins unlocked");
+      }
+      refresh();
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to unlock saved logins: " + getErrorMessage(e));
+      }
+    }
+  };
+
   const logOutAll = async () => {
     try {
       await invoke("do_logout");
@@ -26,19 +52,23 @@ export default function AuthenticationTab({ active }: { active: boolean }) {
       refresh();
     } catch (e) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to log out of all game servers: " + e);
+        ctx.alertError(
+          "Failed to log out of all game servers: " + getErrorMessage(e)
+        );
       }
     }
   };
 
   const refresh = async () => {
     fetchServers();
+    fetchTokenStoreStatus();
     setRefreshes((refreshes) => refreshes + 1);
   };
 
   useEffect(() => {
     if (!servers && active) {
       fetchServers();
+      fetchTokenStoreStatus();
     }
   }, [active]);
 
@@ -57,6 +87,15 @@ export default function AuthenticationTab({ active }: { active: boolean }) {
           variant="primary"
           onClick={refresh}
         />
+        {tokenStore?.locked && (
+          <Button
+            icon="lock"
+            text="Unlock Logins"
+            tooltip="Enter your passphrase to use saved logins"
+            variant="success"
+            onClick={() => setShowUnlockModal(true)}
+          />
+        )}
         {/* <div className="p-2 ms-auto"></div> */}
         <Button
           icon="sign-out-alt"
@@ -76,6 +115,11 @@ export default function AuthenticationTab({ active }: { active: boolean }) {
         />
       </Stack>
       <AuthenticationList servers={servers} refreshes={refreshes} />
+      <UnlockTokensModal
+        show={showUnlockModal}
+        setShow={setShowUnlockModal}
+        onSubmit={unlockTokens}
+      />
     </>
   );
 }
@@ -0,0 +1,215 @@
+import { Form, Modal } from "react-bootstrap";
+import Button from "@/components/Button";
+import { useState, useEffect, useContext } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import {
+  CacheCollectResult,
+  CacheDirEntry,
+  CacheDirKind,
+  CacheScanReport,
+  EvictionPlan,
+} from "@/app/types";
+import { SettingsCtx } from "@/app/contexts";
+import { getErrorMessage } from "@/app/util";
+
+const BYTES_PER_GB = 1024 * 1024 * 1024;
+
+const formatBytesToGB = (bytes: number) => {
+  return (bytes / BYTES_PER_GB).toFixed(2) + " GB";
+};
+
+const getKindLabel = (kind: CacheDirKind) => {
+  switch (kind) {
+    case "orphan":
+      return "Build removed";
+    case "unused":
+      return "No server uses this build";
+    case "unknown":
+      return "Not a build cache";
+  }
+};
+
+const getKey = (entry: CacheDirEntry) => {
+  return (entry.offline ? "offline/" : "game/") + entry.name;
+};
+
+export default function CacheCleanupModal({
+  show,
+  setShow,
+  onCollected,
+}: {
+  show: boolean;
+  setShow: (show: boolean) => void;
+  onCollected: (entries: CacheDirEntry[]) => void;
+}) {
+  const [report, setReport] = useState<CacheScanReport | undefined>(
+    undefined,
+  );
+  const [plan, setPlan] = useState<EvictionPlan | undefined>(undefined);
+  const [selected, setSelected] = useState<string[]>([]);
+  const [deleting, setDeleting] = useState<boolean>(false);
+
+  const ctx = useContext(SettingsCtx);
+
+  const scan = async () => {
+    setReport(undefined);
+    try {
+      const report: CacheScanReport = await invoke("scan_caches");
+      setReport(report);
+      // caches of removed builds are safe bets; leave the rest to the user
+      setSelected(
+        report.entries.filter((e) => e.kind == "orphan").map(getKey),
+      );
+      const plan: EvictionPlan = await invoke("get_cache_eviction_plan", {
+        serverUuid: null,
+      });
+      setPlan(plan);
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to scan caches: " + getErrorMessage(e));
+      }
+      setShow(false);
+    }
+  };
+
+  useEffect(() => {
+    if (show) {
+      scan();
+    }
+  }, [show]);
+
+  const getQuotaSummary = (plan: EvictionPlan) => {
+    const usage =
+      "Game caches use " +
+      formatBytesToGB(plan.total_bytes) +
+      " of the " +
+      formatBytesToGB(plan.quota!) +
+      " limit.";
+    const evicted = plan.entries.filter((e) => e.evict);
+    if (evicted.length == 0) {
+      return usage;
+    }
+    return (
+      usage +
+      " The next launch will delete up to " +
+      formatBytesToGB(plan.evicted_bytes) +
+      " from the least recently used: " +
+      evicted.map((e) => e.version_name ?? e.uuid).join(", ") +
+      "."
+    );
+  };
+
+  const toggle = (entry: CacheDirEntry) => {
+    const key = getKey(entry);
+    setSelected((prev) =>
+      prev.includes(key) ? prev.filter((k) => k != key) : [...prev, key],
+    );
+  };
+
+  const selectedEntries =
+    report?.entries.filter((e) => selected.includes(getKey(e))) ?? [];
+  const selectedBytes = selectedEntries.reduce((acc, e) => acc + e.size, 0);
+
+  const deleteSelected = async () => {
+    setDeleting(true);
+    const targets = selectedEntries.map((e) => ({
+      name: e.name,
+      offline: e.offline,
+    }));
+    const result: CacheCollectResult = await invoke("collect_caches", {
+      targets,
+    });
+    setDeleting(false);
+    onCollected(selectedEntries);
+    if (result.skipped.length > 0 && ctx.alertError) {
+      ctx.alertError(
+        "Some caches were in use or couldn't be deleted: " +
+          result.skipped.join(", "),
+      );
+    }
+    if (ctx.alertSuccess) {
+      ctx.alertSuccess(
+        "Deleted " +
+          result.deleted +
+          " cache(s), freeing " +
+          formatBytesToGB(result.freed_bytes),
+      );
+    }
+    setShow(false);
+  };
+
+  return (
+    <Modal show={show} onHide={() => setShow(false)} centered size="lg">
+      <Modal.Header closeButton>
+        <Modal.Title>Clean Up Caches</Modal.Title>
+      </Modal.Header>
+      <Modal.Body className="p-0">
+        {report && (
+          <p className="px-3 pt-3">
+            {report.entries.length == 0
+              ? "Nothing to clean up."
+              : formatBytesToGB(report.reclaimable_bytes) +
+                " can be reclaimed. Builds used by servers are never listed, but endpoint servers only count the build you last picked for them."}
+          </p>
+        )}
+        {plan?.quota != undefined && (
+          <p className="px-3">{getQuotaSummary(plan)}</p>
+        )}
+        <div className="table-responsive">
+          <table className="table table-striped mb-0">
+            <tbody>
+              {!report ? (
+                <tr>
+                  <td className="text-center">
+                    <span
+                      className="spinner-border spinner-border-sm m-2"
+                      role="status"
+                      aria-hidden="true"
+                    ></span>
+                  </td>
+                </tr>
+              ) : (
+                report.entries.map((entry) => (
+                  <tr key={getKey(entry)}>
+                    <td className="align-middle">
+                      <Form.Check
+                        id={"gc-" + getKey(entry)}
+                        checked={selected.includes(getKey(entry))}
+                        onChange={() => toggle(entry)}
+                      />
+                    </td>
+                    <td>
+                      {entry.version_name ?? entry.name}
+                      <h6 className="mb-0 text-muted">
+                        {(entry.offline ? "Offline" : "Game") +
+                          " cache · " +
+                          getKindLabel(entry.kind)}
+                      </h6>
+                    </td>
+                    <td className="text-end align-middle">
+                      {formatBytesToGB(entry.size)}
+                    </td>
+                  </tr>
+                ))
+              )}
+            </tbody>
+          </table>
+        </div>
+      </Modal.Body>
+      <Modal.Footer>
+        <Button
+          variant="primary"
+          onClick={() => setShow(false)}
+          text="Cancel"
+        />
+        <Button
+          variant="danger"
+          text={"Delete Selected (" + formatBytesToGB(selectedBytes) + ")"}
+          loading={deleting}
+          enabled={selectedEntries.length > 0}
+          onClick={deleteSelected}
+        />
+      </Modal.Footer>
+    </Modal>
+  );
+}
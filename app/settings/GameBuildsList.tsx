@@ -126,17 +126,23 @@ export default function GameBuildsList({
   versions,
   versionDataList,
   clearGameCache,
+  downloadGameCache,
   downloadOfflineCache,
   repairOfflineCache,
   deleteOfflineCache,
+  exportOfflineCache,
+  cancelCacheOp,
   removeVersion,
 }: {
   versions?: VersionEntry[];
   versionDataList: VersionCacheData[];
   clearGameCache: (uuid: string) => void;
+  downloadGameCache: (uuid: string, repair: boolean) => void;
   downloadOfflineCache: (uuid: string) => void;
   repairOfflineCache: (uuid: string) => void;
   deleteOfflineCache: (uuid: string) => void;
+  exportOfflineCache: (uuid: string) => void;
+  cancelCacheOp: (uuid: string, offline: boolean) => void;
   removeVersion: (uuid: string) => void;
 }) {
   return (
@@ -237,6 +243,23 @@ export default function GameBuildsList({
                         onClick={() => invoke("open_folder_for_version", { uuid: version.uuid, offline: false })}
                         tooltip="Open game cache folder"
                       />{" "}
+                      <Button
+                        loading={!versionData.gameDone}
+                        enabled={
+                          (!getValidatedSize(versionData.gameItems) ||
+                            isCorrupt(versionData.gameItems, true)) &&
+                          !!version.total_uncompressed_size
+                        }
+                        icon="download"
+                        onClick={() =>
+                          downloadGameCache(
+                            version.uuid,
+                            Object.keys(versionData.gameItems).length > 0,
+                          )
+                        }
+                        variant="success"
+                        tooltip="Download and unpack game cache"
+                      />{" "}
                       <Button
                         loading={!versionData.gameDone}
                         enabled={!!getValidatedSize(versionData.gameItems)}
@@ -245,6 +268,17 @@ export default function GameBuildsList({
                         variant="danger"
                         tooltip="Clear game cache"
                       />
+                      {!versionData.gameDone && (
+                        <>
+                          {" "}
+                          <Button
+                            icon="stop"
+                            onClick={() => cancelCacheOp(version.uuid, false)}
+                            variant="secondary"
+                            tooltip="Stop"
+                          />
+                        </>
+                      )}
                     </td>
                     <td className="text-center cache-col">
                       <p>
@@ -300,7 +334,18 @@ export default function GameBuildsList({
                         icon="screwdriver-wrench"
                         onClick={() => repairOfflineCache(version.uuid)}
                         variant="warning"
-                        tooltip="Repair offline cache"
+                        tooltip="Repair offline cache or download the files it's missing"
+                      />{" "}
+                      <Button
+                        loading={!versionData.offlineDone}
+                        enabled={
+                          !!getValidatedSize(versionData.offlineItems) &&
+                          !isCorrupt(versionData.offlineItems, true)
+                        }
+                        icon="file-export"
+                        onClick={() => exportOfflineCache(version.uuid)}
+                        variant="primary"
+                        tooltip="Export offline cache to an archive"
                       />{" "}
                       <Button
                         loading={!versionData.offlineDone}
@@ -310,6 +355,17 @@ export default function GameBuildsList({
                         variant="danger"
                         tooltip="Delete offline cache"
                       />
+                      {!versionData.offlineDone && (
+                        <>
+                          {" "}
+                          <Button
+                            icon="stop"
+                            onClick={() => cancelCacheOp(version.uuid, true)}
+                            variant="secondary"
+                            tooltip="Stop"
+                          />
+                        </>
+                      )}
                     </td>
                     <td className="text-end p-1">
                       <Button
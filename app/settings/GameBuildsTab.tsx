@@ -1,18 +1,26 @@
 import { useState, useEffect, useContext } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "@/app/util";
 import {
+  CacheDirEntry,
+  ValidationReport,
   VersionCacheData,
   VersionCacheProgress,
   VersionEntry,
+  VersionUpdate,
   Versions,
 } from "@/app/types";
 import GameBuildsList from "./GameBuildsList";
 import { SettingsCtx } from "@/app/contexts";
 import { listen } from "@tauri-apps/api/event";
+import { open, save } from "@tauri-apps/plugin-dialog";
 import { Stack } from "react-bootstrap";
 import Button from "@/components/Button";
 import AddBuildModal from "./AddBuildModal";
 import RemoveBuildModal from "./RemoveBuildModal";
+import TrustedKeysModal from "./TrustedKeysModal";
+import VersionDetailsModal from "./VersionDetailsModal";
+import CacheCleanupModal from "./CacheCleanupModal";
 
 const findVersion = (versions: VersionEntry[], uuid: string) => {
   return versions.find((version) => version.uuid == uuid);
@@ -26,8 +34,14 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
 
   const [showAddBuildModal, setShowAddBuildModal] = useState(false);
   const [showRemoveBuildModal, setShowRemoveBuildModal] = useState(false);
+  const [showTrustedKeysModal, setShowTrustedKeysModal] = useState(false);
+  const [showDetailsModal, setShowDetailsModal] = useState(false);
+  const [showCleanupModal, setShowCleanupModal] = useState(false);
 
   const [removeTarget, setRemoveTarget] = useState("");
+  const [refreshing, setRefreshing] = useState(false);
+  const [exporting, setExporting] = useState(false);
+  const [importing, setImporting] = useState(false);
 
   const ctx = useContext(SettingsCtx);
 
@@ -49,7 +63,9 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
       });
     } catch (e) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to clear game cache" + txt + ": " + e);
+        ctx.alertError(
+          "Failed to clear game cache" + txt + ": " + getErrorMessage(e)
+        );
       }
     }
   };
@@ -68,6 +84,23 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
     }
   };
 
+  const downloadGameCache = async (uuid: string, repair: boolean) => {
+    try {
+      await invoke("download_cache", { uuid, offline: false, repair });
+      setVersionData((prev) => {
+        return prev.map((pv) =>
+          pv.versionUuid == uuid ? { ...pv, gameDone: false } : pv,
+        );
+      });
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError(
+          "Failed to kickoff game cache download: " + getErrorMessage(e)
+        );
+      }
+    }
+  };
+
   const downloadOfflineCache = async (uuid: string) => {
     try {
       await invoke("download_cache", { uuid, offline: true, repair: false });
@@ -78,20 +111,54 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
       });
     } catch (e) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to kickoff offline cache download: " + e);
+        ctx.alertError(
+          "Failed to kickoff offline cache download: " + getErrorMessage(e)
+        );
       }
     }
   };
 
   const repairOfflineCache = async (uuid: string) => {
     try {
+      const report: ValidationReport | null = await invoke(
+        "get_validation_report",
+        { uuid, offline: true },
+      );
       await invoke("download_cache", { uuid, offline: true, repair: true });
       if (ctx.alertSuccess) {
-        ctx.alertSuccess("Offline cache repair started");
+        // the repair only fetches what the last validation flagged
+        const flagged = report
+          ? Object.values(report.items).filter((item) => item.corrupt).length
+          : 0;
+        ctx.alertSuccess(
+          flagged > 0
+            ? "Offline cache repair started (" + flagged + " flagged file(s))"
+            : "Offline cache repair started",
+        );
+      }
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError(
+          "Failed to kickoff offline cache repair: " + getErrorMessage(e)
+        );
+      }
+    }
+  };
+
+  const cancelCacheOp = async (uuid: string, offline: boolean) => {
+    try {
+      const cancelled: boolean = await invoke("cancel_cache_op", {
+        uuid,
+        offline,
+      });
+      if (cancelled && ctx.alertSuccess) {
+        ctx.alertSuccess(
+          "Stopped. Finished files are kept; the file that was downloading starts over next time",
+        );
       }
     } catch (e) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to kickoff offline cache repair: " + e);
+        ctx.alertError("Failed to stop cache operation: " + getErrorMessage(e));
       }
     }
   };
@@ -118,9 +185,78 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
       });
     } catch (e) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to delete offline cache" + txt + ": " + e);
+        ctx.alertError(
+          "Failed to delete offline cache" + txt + ": " + getErrorMessage(e)
+        );
+      }
+    }
+  };
+
+  const exportOfflineCache = async (uuid: string) => {
+    const version = versions!.find((v) => v.uuid == uuid)!;
+    const path = await save({
+      defaultPath: (version.name ?? version.uuid) + ".tar",
+      filters: [
+        {
+          name: "Offline cache archive",
+          extensions: ["tar"],
+        },
+      ],
+    });
+    if (!path) {
+      return;
+    }
+    setExporting(true);
+    try {
+      await invoke("export_offline_cache", { uuid, path });
+      if (ctx.alertSuccess) {
+        ctx.alertSuccess("Offline cache exported to " + path);
+      }
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to export offline cache: " + getErrorMessage(e));
+      }
+    }
+    setExporting(false);
+  };
+
+  const importOfflineCache = async () => {
+    const path = await open({
+      multiple: false,
+      directory: false,
+      filters: [
+        {
+          name: "Offline cache archive",
+          extensions: ["tar"],
+        },
+      ],
+    });
+    if (!path) {
+      return;
+    }
+    setImporting(true);
+    try {
+      // every file is checked against the manifest before anything is kept
+      const versionLabel: string = await invoke("import_offline_cache", {
+        path,
+      });
+      // new builds get validated once they show up; known ones need a fresh look
+      const known = versions?.find(
+        (v) => v.name == versionLabel || v.uuid == versionLabel,
+      );
+      await fetchVersions();
+      if (known) {
+        invoke("validate_cache", { uuid: known.uuid, offline: true });
+      }
+      if (ctx.alertSuccess) {
+        ctx.alertSuccess("Imported offline cache for " + versionLabel);
+      }
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to import offline cache: " + getErrorMessage(e));
       }
     }
+    setImporting(false);
   };
 
   const deleteAllOfflineCaches = async () => {
@@ -133,6 +269,14 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
     }
   };
 
+  const revalidateCollected = (entries: CacheDirEntry[]) => {
+    for (const entry of entries) {
+      if (versions?.find((v) => v.uuid == entry.name)) {
+        invoke("validate_cache", { uuid: entry.name, offline: entry.offline });
+      }
+    }
+  };
+
   const handleProgress = (progress: VersionCacheProgress) => {
     setVersionData((prev) => {
       const ppv = prev.find((pv) => pv.versionUuid == progress.uuid);
@@ -144,15 +288,17 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
         offlineItems: {},
       };
       const isDone = progress.done;
+      // a cancelled run only saw some of the files, so its results say nothing about the rest
+      const items = progress.cancelled ? {} : progress.items;
       const nv: VersionCacheData = progress.offline
         ? {
             ...pv,
-            offlineItems: progress.items,
+            offlineItems: items,
             offlineDone: isDone,
           }
         : {
             ...pv,
-            gameItems: progress.items,
+            gameItems: items,
             gameDone: isDone,
           };
 
@@ -181,7 +327,7 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
       return true;
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to import build: " + e);
+        ctx.alertError("Failed to import build: " + getErrorMessage(e));
       }
     }
     return false;
@@ -199,23 +345,56 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
       }
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to remove build: " + e);
+        ctx.alertError("Failed to remove build: " + getErrorMessage(e));
       }
     }
   };
 
-  const addBuildManual = async (name: string, assetUrl: string) => {
+  const addBuildManual = async (
+    name: string,
+    assetUrl: string,
+    mirrors: string[],
+  ) => {
     try {
-      await invoke("add_version_manual", { name, assetUrl });
+      await invoke("add_version_manual", { name, assetUrl, mirrors });
       await fetchVersions();
       if (ctx.alertSuccess) {
         ctx.alertSuccess("Added build " + name);
       }
     } catch (e: unknown) {
       if (ctx.alertError) {
-        ctx.alertError("Failed to add build: " + e);
+        ctx.alertError("Failed to add build: " + getErrorMessage(e));
+      }
+    }
+  };
+
+  const refreshManifests = async () => {
+    setRefreshing(true);
+    try {
+      const updates: VersionUpdate[] = await invoke("refresh_versions", {
+        serverUuid: null,
+      });
+      if (updates.length > 0) {
+        await fetchVersions();
+      }
+      if (ctx.alertSuccess) {
+        if (updates.length == 0) {
+          ctx.alertSuccess("All build manifests are up to date");
+        } else {
+          const details = updates
+            .map((u) => u.name + " (" + u.changes.join(", ") + ")")
+            .join("; ");
+          ctx.alertSuccess(
+            "Updated " + updates.length + " build manifest(s): " + details,
+          );
+        }
+      }
+    } catch (e: unknown) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to refresh build manifests: " + getErrorMessage(e));
       }
     }
+    setRefreshing(false);
   };
 
   const fetchVersions = async () => {
@@ -280,7 +459,44 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
           variant="success"
           onClick={() => setShowAddBuildModal(true)}
         />
+        <Button
+          icon="circle-info"
+          text="Details"
+          tooltip="Show where builds came from and how much space they use"
+          variant="primary"
+          onClick={() => setShowDetailsModal(true)}
+        />
+        <Button
+          icon="key"
+          text="Trusted Keys"
+          tooltip="Manage the keys build manifests can be signed with"
+          variant="primary"
+          onClick={() => setShowTrustedKeysModal(true)}
+        />
+        <Button
+          icon="rotate-right"
+          text="Refresh Manifests"
+          tooltip="Re-download build manifests from the servers that publish them"
+          variant="primary"
+          loading={refreshing}
+          onClick={refreshManifests}
+        />
+        <Button
+          icon="file-import"
+          text="Import Offline Cache"
+          tooltip="Import an offline cache archive exported from another machine"
+          variant="primary"
+          loading={importing || exporting}
+          onClick={importOfflineCache}
+        />
         <div className="p-2 ms-auto"></div>
+        <Button
+          icon="broom"
+          text="Clean Up"
+          tooltip="Find and delete caches that aren't needed anymore"
+          variant="danger"
+          onClick={() => setShowCleanupModal(true)}
+        />
         <Button
           icon="trash"
           text="Delete All Offline"
@@ -331,8 +547,11 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
             );
           }
         }}
+        downloadGameCache={downloadGameCache}
         downloadOfflineCache={downloadOfflineCache}
         repairOfflineCache={repairOfflineCache}
+        exportOfflineCache={exportOfflineCache}
+        cancelCacheOp={cancelCacheOp}
         deleteOfflineCache={(uuid) => {
           if (ctx.showConfirmationModal) {
             const version = versions!.find((v) => v.uuid == uuid)!;
@@ -361,6 +580,19 @@ export default function GameBuildsTab({ active }: { active: boolean }) {
         version={findVersion(versions ?? [], removeTarget)}
         onConfirm={removeBuild}
       />
+      <TrustedKeysModal
+        show={showTrustedKeysModal}
+        setShow={setShowTrustedKeysModal}
+      />
+      <VersionDetailsModal
+        show={showDetailsModal}
+        setShow={setShowDetailsModal}
+      />
+      <CacheCleanupModal
+        show={showCleanupModal}
+        setShow={setShowCleanupModal}
+        onCollected={revalidateCollected}
+      />
     </>
   );
 }
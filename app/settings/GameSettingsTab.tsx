@@ -3,7 +3,7 @@ import { useContext, useEffect, useState } from "react";
 import { Col, Container, Form, Row } from "react-bootstrap";
 import SettingControlDropdown from "./SettingControlDropdown";
 import SettingControlWindowSize from "./SettingControlWindowSize";
-import { deepEqual, getDebugMode } from "@/app/util";
+import { deepEqual, getDebugMode, getErrorMessage } from "@/app/util";
 import SettingsHeader from "./SettingsHeader";
 import { SettingsCtx } from "@/app/contexts";
 import SettingControlFpsFix from "./SettingControlFpsFix";
@@ -101,7 +101,9 @@ export default function GameSettingsTab({
         });
       } catch (e: unknown) {
         if (ctx.alertError) {
-          ctx.alertError("Failed to update launch profile: (" + e + ")");
+          ctx.alertError(
+            "Failed to update launch profile: (" + getErrorMessage(e) + ")"
+          );
         }
       }
     } else {
@@ -3,10 +3,52 @@ import { useContext, useEffect, useState } from "react";
 import { Col, Container, Form, Row } from "react-bootstrap";
 import SettingControlDropdown from "./SettingControlDropdown";
 import SettingControlBrowse from "./SettingControlBrowse";
+import SettingControlText from "./SettingControlText";
 import { getDebugMode } from "@/app/util";
 import { SettingsCtx } from "@/app/contexts";
 import SettingsHeader from "./SettingsHeader";
 
+const BYTES_PER_GB = 1024 * 1024 * 1024;
+
+const quotaToText = (quota?: number) => {
+  if (quota == undefined) {
+    return undefined;
+  }
+  return parseFloat((quota / BYTES_PER_GB).toFixed(2)).toString();
+};
+
+const textToQuota = (text: string) => {
+  if (text.trim() == "") {
+    return undefined;
+  }
+  return Math.round(parseFloat(text) * BYTES_PER_GB);
+};
+
+const BYTES_PER_MB = 1024 * 1024;
+
+const speedLimitToText = (limit?: number) => {
+  if (limit == undefined) {
+    return undefined;
+  }
+  return parseFloat((limit / BYTES_PER_MB).toFixed(2)).toString();
+};
+
+const textToSpeedLimit = (text: string) => {
+  if (text.trim() == "") {
+    return undefined;
+  }
+  const limit = Math.round(parseFloat(text) * BYTES_PER_MB);
+  return limit > 0 ? limit : undefined;
+};
+
+const getCountOptions = (max: number) => {
+  return Array.from({ length: max }, (_, i) => ({
+    key: (i + 1).toString(),
+    value: i + 1,
+    label: (i + 1).toString(),
+  }));
+};
+
 export default function LauncherSettingsTab({
   active,
   currentSettings,
@@ -165,6 +207,63 @@ export default function LauncherSettingsTab({
                   }))
                 }
               />
+              <SettingControlDropdown
+                id="address_family"
+                name="Server address preference"
+                options={[
+                  {
+                    key: "prefer_ipv4",
+                    label: "Prefer IPv4",
+                    description: "use IPv6 only if a server has no IPv4 address",
+                  },
+                  {
+                    key: "prefer_ipv6",
+                    label: "Prefer IPv6",
+                    description: "use IPv4 only if a server has no IPv6 address",
+                  },
+                  { key: "ipv4_only", label: "IPv4 only" },
+                  { key: "ipv6_only", label: "IPv6 only" },
+                ]}
+                defaultKey="prefer_ipv4"
+                oldValue={currentSettings.address_family}
+                value={settings.address_family}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    address_family: value,
+                  }))
+                }
+              />
+              <SettingControlDropdown
+                id="manifest_signatures"
+                name="Untrusted build manifests"
+                options={[
+                  {
+                    key: "warn",
+                    label: "Warn",
+                    description: "accept manifests not signed by a trusted key, but warn",
+                  },
+                  {
+                    key: "require",
+                    label: "Refuse",
+                    description: "only accept manifests signed by a trusted key",
+                  },
+                  {
+                    key: "off",
+                    label: "Allow",
+                    description: "don't check manifest signatures",
+                  },
+                ]}
+                defaultKey="warn"
+                oldValue={currentSettings.manifest_signatures}
+                value={settings.manifest_signatures}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    manifest_signatures: value,
+                  }))
+                }
+              />
               <SettingControlDropdown
                 id="use_offline_caches"
                 name="Use offline caches when downloaded"
@@ -199,6 +298,43 @@ export default function LauncherSettingsTab({
                   }))
                 }
               />
+              <SettingControlDropdown
+                id="background_cache_verification"
+                name="Verify installed caches in the background while idle"
+                options={[
+                  { key: "yes", value: true, label: "Yes" },
+                  { key: "no", value: false, label: "No" },
+                ]}
+                defaultKey="no"
+                oldValue={currentSettings.background_cache_verification}
+                value={settings.background_cache_verification}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    background_cache_verification: value,
+                  }))
+                }
+              />
+              <SettingControlDropdown
+                id="cache_verification_interval_hours"
+                name="Background verification interval"
+                options={[
+                  { key: "6", value: 6, label: "Every 6 hours" },
+                  { key: "12", value: 12, label: "Every 12 hours" },
+                  { key: "24", value: 24, label: "Daily" },
+                  { key: "72", value: 72, label: "Every 3 days" },
+                  { key: "168", value: 168, label: "Weekly" },
+                ]}
+                defaultKey="24"
+                oldValue={currentSettings.cache_verification_interval_hours}
+                value={settings.cache_verification_interval_hours}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    cache_verification_interval_hours: value,
+                  }))
+                }
+              />
               <SettingControlDropdown
                 id="delete_old_game_caches"
                 name="Delete old game caches on upgrades"
@@ -216,6 +352,62 @@ export default function LauncherSettingsTab({
                   }))
                 }
               />
+              <SettingControlText
+                id="game_cache_quota"
+                name="Game cache size limit in GB (least recently used caches are deleted on launch)"
+                placeholder="No limit"
+                oldValue={quotaToText(currentSettings.game_cache_quota)}
+                value={quotaToText(settings.game_cache_quota)}
+                validator={(value) => /^(\d+(\.\d*)?)?$/.test(value.trim())}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    game_cache_quota: textToQuota(value),
+                  }))
+                }
+              />
+              <SettingControlDropdown
+                id="max_concurrent_downloads"
+                name="Simultaneous cache downloads"
+                options={getCountOptions(4)}
+                defaultKey="1"
+                oldValue={currentSettings.max_concurrent_downloads}
+                value={settings.max_concurrent_downloads}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    max_concurrent_downloads: value,
+                  }))
+                }
+              />
+              <SettingControlDropdown
+                id="max_concurrent_validations"
+                name="Simultaneous cache validations (HDDs do best with 1)"
+                options={getCountOptions(5)}
+                defaultKey="2"
+                oldValue={currentSettings.max_concurrent_validations}
+                value={settings.max_concurrent_validations}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    max_concurrent_validations: value,
+                  }))
+                }
+              />
+              <SettingControlText
+                id="download_speed_limit"
+                name="Download speed limit in MB/s (offline cache downloads and proxied game downloads)"
+                placeholder="No limit"
+                oldValue={speedLimitToText(currentSettings.download_speed_limit)}
+                value={speedLimitToText(settings.download_speed_limit)}
+                validator={(value) => /^(\d+(\.\d*)?)?$/.test(value.trim())}
+                onChange={(value) =>
+                  setSettings((current) => ({
+                    ...current!,
+                    download_speed_limit: textToSpeedLimit(value),
+                  }))
+                }
+              />
               <SettingControlDropdown
                 id="launch_behavior"
                 name="Launch behavior"
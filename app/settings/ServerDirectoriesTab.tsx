@@ -0,0 +1,190 @@
+import { useState, useEffect, useContext } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { getErrorMessage } from "@/app/util";
+import { SettingsCtx } from "@/app/contexts";
+import { Stack } from "react-bootstrap";
+import Button from "@/components/Button";
+import { Servers } from "@/app/types";
+import AddDirectoryModal from "./AddDirectoryModal";
+
+const formatTimestamp = (timestamp?: number) => {
+  if (!timestamp) {
+    return "Never";
+  }
+  return new Date(timestamp * 1000).toLocaleString();
+};
+
+export default function ServerDirectoriesTab({ active }: { active: boolean }) {
+  const [servers, setServers] = useState<Servers | undefined>(undefined);
+  const [syncing, setSyncing] = useState(false);
+  const [showAddModal, setShowAddModal] = useState(false);
+
+  const ctx = useContext(SettingsCtx);
+
+  const fetchServers = async () => {
+    const servers: Servers = await invoke("get_servers");
+    setServers(servers);
+  };
+
+  const addDirectory = async (url: string, publicKey: string) => {
+    try {
+      await invoke("add_server_directory", { url, publicKey });
+      if (ctx.alertSuccess) {
+        ctx.alertSuccess("Subscribed to " + url);
+      }
+      fetchServers();
+      return true;
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to add server directory: " + getErrorMessage(e));
+      }
+      return false;
+    }
+  };
+
+  const removeDirectory = async (uuid: string, url: string) => {
+    await invoke("remove_server_directory", { uuid });
+    if (ctx.alertSuccess) {
+      ctx.alertSuccess("Unsubscribed from " + url);
+    }
+    fetchServers();
+  };
+
+  const syncNow = async () => {
+    setSyncing(true);
+    await invoke("sync_server_directories");
+    await fetchServers();
+    setSyncing(false);
+  };
+
+  const restoreHidden = async () => {
+    setSyncing(true);
+    await invoke("restore_hidden_servers");
+    await fetchServers();
+    setSyncing(false);
+    if (ctx.alertSuccess) {
+      ctx.alertSuccess("Hidden servers restored");
+    }
+  };
+
+  const getServerCount = (directoryUuid: string) => {
+    return servers!.servers.filter((s) => s.managed_by == directoryUuid)
+      .length;
+  };
+
+  useEffect(() => {
+    if (!servers && active) {
+      fetchServers();
+    }
+  }, [active]);
+
+  return (
+    <>
+      <Stack
+        direction="horizontal"
+        className="flex-row-reverse p-2"
+        gap={2}
+        id="game-builds-buttonstack"
+      >
+        <Button
+          icon="plus"
+          text="Add Directory"
+          tooltip="Subscribe to a server directory"
+          variant="success"
+          onClick={() => setShowAddModal(true)}
+        />
+        <Button
+          icon="rotate-right"
+          text="Sync Now"
+          tooltip="Fetch all server directories now"
+          variant="primary"
+          loading={syncing}
+          onClick={syncNow}
+        />
+        <Button
+          icon="eye"
+          text="Restore Hidden"
+          tooltip="Bring back directory servers you deleted"
+          variant="primary"
+          enabled={(servers?.hidden.length ?? 0) > 0 && !syncing}
+          onClick={restoreHidden}
+        />
+      </Stack>
+      <div className="table-responsive" id="builds-table">
+        <table className="table table-striped table-hover mb-0">
+          <thead>
+            <tr>
+              <th>Directory</th>
+              <th className="text-center">Servers</th>
+              <th className="text-center">Last Synced</th>
+              <th className="text-end"></th>
+            </tr>
+          </thead>
+          <tbody>
+            {!servers ? (
+              <tr>
+                <td colSpan={4} className="text-center">
+                  <span
+                    className="spinner-border spinner-border-sm m-2"
+                    role="status"
+                    aria-hidden="true"
+                  ></span>
+                </td>
+              </tr>
+            ) : servers.directories.length == 0 ? (
+              <tr>
+                <td colSpan={4} className="text-center">
+                  Not subscribed to any server directories.
+                </td>
+              </tr>
+            ) : (
+              servers.directories.map((directory) => (
+                <tr key={directory.uuid}>
+                  <td className="font-monospace align-middle">
+                    {directory.url}
+                    {directory.last_error && (
+                      <i
+                        className="fas fa-triangle-exclamation text-danger ms-2"
+                        title={directory.last_error}
+                      ></i>
+                    )}
+                  </td>
+                  <td className="text-center align-middle">
+                    {getServerCount(directory.uuid)}
+                  </td>
+                  <td className="text-center align-middle">
+                    {formatTimestamp(directory.last_synced)}
+                  </td>
+                  <td className="text-end">
+                    <Button
+                      icon="trash"
+                      tooltip="Unsubscribe and remove its servers"
+                      variant="danger"
+                      onClick={() => {
+                        if (ctx.showConfirmationModal) {
+                          ctx.showConfirmationModal(
+                            "Unsubscribe from " +
+                              directory.url +
+                              "? Its servers will be removed from your list.",
+                            "Unsubscribe",
+                            "danger",
+                            () => removeDirectory(directory.uuid, directory.url),
+                          );
+                        }
+                      }}
+                    />
+                  </td>
+                </tr>
+              ))
+            )}
+          </tbody>
+        </table>
+      </div>
+      <AddDirectoryModal
+        show={showAddModal}
+        setShow={setShowAddModal}
+        onSubmit={addDirectory}
+      />
+    </>
+  );
+}
@@ -0,0 +1,141 @@
+import { Form, Modal, Stack } from "react-bootstrap";
+import Button from "@/components/Button";
+import { useState, useEffect, useContext } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { TrustedKey } from "@/app/types";
+import { SettingsCtx } from "@/app/contexts";
+import { getErrorMessage } from "@/app/util";
+
+export default function TrustedKeysModal({
+  show,
+  setShow,
+}: {
+  show: boolean;
+  setShow: (show: boolean) => void;
+}) {
+  const [keys, setKeys] = useState<TrustedKey[] | undefined>(undefined);
+  const [name, setName] = useState<string>("");
+  const [publicKey, setPublicKey] = useState<string>("");
+
+  const ctx = useContext(SettingsCtx);
+
+  const fetchKeys = async () => {
+    const keys: TrustedKey[] = await invoke("get_trusted_keys");
+    setKeys(keys);
+  };
+
+  useEffect(() => {
+    if (show) {
+      setName("");
+      setPublicKey("");
+      fetchKeys();
+    }
+  }, [show]);
+
+  const addKey = async () => {
+    try {
+      await invoke("add_trusted_key", {
+        name: name.trim(),
+        publicKey: publicKey.trim(),
+      });
+      setName("");
+      setPublicKey("");
+      fetchKeys();
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to add trusted key: " + getErrorMessage(e));
+      }
+    }
+  };
+
+  const removeKey = async (key: TrustedKey) => {
+    try {
+      await invoke("remove_trusted_key", { publicKey: key.public_key });
+      fetchKeys();
+    } catch (e) {
+      if (ctx.alertError) {
+        ctx.alertError("Failed to remove trusted key: " + getErrorMessage(e));
+      }
+    }
+  };
+
+  return (
+    <Modal show={show} onHide={() => setShow(false)} centered size="lg">
+      <Modal.Header closeButton>
+        <Modal.Title>Trusted Publisher Keys</Modal.Title>
+      </Modal.Header>
+      <Modal.Body className="p-0">
+        <p className="px-3 pt-3">
+          Build manifests signed with one of these keys are trusted. Keys that
+          ship with the launcher can&apos;t be removed.
+        </p>
+        <div className="table-responsive">
+          <table className="table table-striped mb-0">
+            <tbody>
+              {keys?.length == 0 && (
+                <tr>
+                  <td className="text-center">No trusted keys.</td>
+                </tr>
+              )}
+              {keys?.map((key) => (
+                <tr key={key.public_key}>
+                  <td className="align-middle">
+                    {key.name}
+                    {key.builtin && (
+                      <span className="badge bg-secondary ms-2">builtin</span>
+                    )}
+                    <h6 className="mb-0 text-muted font-monospace">
+                      {key.public_key}
+                    </h6>
+                  </td>
+                  <td className="text-end align-middle">
+                    <Button
+                      icon="trash"
+                      tooltip="Remove key"
+                      variant="danger"
+                      enabled={!key.builtin}
+                      onClick={() => removeKey(key)}
+                    />
+                  </td>
+                </tr>
+              ))}
+            </tbody>
+          </table>
+        </div>
+        <Form
+          className="p-3"
+          onSubmit={(e) => {
+            e.preventDefault();
+            addKey();
+          }}
+        >
+          <Stack direction="horizontal" gap={2}>
+            <Form.Control
+              type="text"
+              value={name}
+              onChange={(e) => setName(e.target.value)}
+              placeholder="Publisher name"
+            />
+            <Form.Control
+              type="text"
+              className="font-monospace"
+              value={publicKey}
+              onChange={(e) => setPublicKey(e.target.value)}
+              placeholder="Base64 ed25519 public key"
+            />
+            <Button
+              icon="plus"
+              tooltip="Trust this key"
+              variant="success"
+              enabled={name.trim().length > 0 && publicKey.trim().length > 0}
+              onClick={addKey}
+            />
+          </Stack>
+        </Form>
+      </Modal.Body>
+      <Modal.Footer>
+        <Button variant="primary" onClick={() => setShow(false)} text="Close" />
+      </Modal.Footer>
+    </Modal>
+  );
+}
@@ -0,0 +1,71 @@
+import { Form, Modal } from "react-bootstrap";
+import Button from "@/components/Button";
+import { useState, useEffect } from "react";
+
+export default function UnlockTokensModal({
+  show,
+  setShow,
+  onSubmit,
+}: {
+  show: boolean;
+  setShow: (show: boolean) => void;
+  onSubmit: (passphrase: string) => Promise<void>;
+}) {
+  const [passphrase, setPassphrase] = useState<string>("");
+  const [loading, setLoading] = useState<boolean>(false);
+
+  useEffect(() => {
+    setPassphrase("");
+  }, [show]);
+
+  const onHitSubmit = async () => {
+    setLoading(true);
+    await onSubmit(passphrase);
+    setLoading(false);
+  };
+
+  return (
+    <Modal show={show} onHide={() => setShow(false)} centered>
+      <Modal.Header closeButton>
+        <Modal.Title>Unlock Saved Logins</Modal.Title>
+      </Modal.Header>
+      <Modal.Body className="p-0">
+        <p className="px-3 pt-3 mb-0">
+          No system keyring is available, so saved logins are encrypted with a
+          passphrase instead. Enter your passphrase below. If you haven't set
+          one yet, the passphrase you enter now will be used from here on.
+        </p>
+        <Form
+          className="p-3"
+          onSubmit={(e) => {
+            e.preventDefault();
+            onHitSubmit();
+          }}
+        >
+          <Form.Group controlId="unlockPassphrase">
+            <Form.Control
+              type="password"
+              value={passphrase}
+              onChange={(e) => setPassphrase(e.target.value)}
+              placeholder="Passphrase"
+            />
+          </Form.Group>
+        </Form>
+      </Modal.Body>
+      <Modal.Footer>
+        <Button
+          variant="primary"
+          onClick={() => setShow(false)}
+          text="Cancel"
+        />
+        <Button
+          variant="success"
+          text="Unlock"
+          loading={loading}
+          enabled={passphrase.length > 0}
+          onClick={() => onHitSubmit()}
+        />
+      </Modal.Footer>
+    </Modal>
+  );
+}
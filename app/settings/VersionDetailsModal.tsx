@@ -0,0 +1,135 @@
+import { Modal } from "react-bootstrap";
+import Button from "@/components/Button";
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { VersionDetails, VersionOrigin } from "@/app/types";
+
+const BYTES_PER_GB = 1024 * 1024 * 1024;
+
+const formatBytesToGB = (bytes?: number) => {
+  if (bytes == undefined) {
+    return "?";
+  }
+  return (bytes / BYTES_PER_GB).toFixed(2) + " GB";
+};
+
+const getOriginLabel = (origin: VersionOrigin) => {
+  switch (origin) {
+    case "builtin":
+      return "Built-in";
+    case "app_data":
+      return "Imported";
+    case "endpoint":
+      return "From server";
+    case "legacy":
+      return "OpenFusionClient";
+  }
+};
+
+export default function VersionDetailsModal({
+  show,
+  setShow,
+}: {
+  show: boolean;
+  setShow: (show: boolean) => void;
+}) {
+  const [details, setDetails] = useState<VersionDetails[] | undefined>(
+    undefined,
+  );
+
+  const fetchDetails = async () => {
+    setDetails(undefined);
+    const details: VersionDetails[] = await invoke("get_version_details");
+    setDetails(details);
+  };
+
+  useEffect(() => {
+    if (show) {
+      fetchDetails();
+    }
+  }, [show]);
+
+  const getName = (uuid: string) => {
+    return details?.find((d) => d.uuid == uuid)?.name ?? uuid;
+  };
+
+  return (
+    <Modal show={show} onHide={() => setShow(false)} centered size="xl">
+      <Modal.Header closeButton>
+        <Modal.Title>Build Details</Modal.Title>
+      </Modal.Header>
+      <Modal.Body className="p-0">
+        <div className="table-responsive">
+          <table className="table table-striped mb-0">
+            <thead>
+              <tr>
+                <th>Build</th>
+                <th>Source</th>
+                <th>Based On</th>
+                <th className="text-end">Download</th>
+                <th className="text-end">Installed</th>
+                <th className="text-end">Game Cache</th>
+                <th className="text-end">Offline Cache</th>
+                <th className="text-center">Servers</th>
+              </tr>
+            </thead>
+            <tbody>
+              {!details ? (
+                <tr>
+                  <td colSpan={8} className="text-center">
+                    <span
+                      className="spinner-border spinner-border-sm m-2"
+                      role="status"
+                      aria-hidden="true"
+                    ></span>
+                  </td>
+                </tr>
+              ) : (
+                details.map((d) => (
+                  <tr key={d.uuid}>
+                    <td>
+                      {d.name}
+                      <h6 className="mb-0 text-muted font-monospace">
+                        {d.uuid}
+                      </h6>
+                      {d.mirrors.length > 0 && (
+                        <h6
+                          className="mb-0 text-muted"
+                          title={d.mirrors.join("\n")}
+                        >
+                          {d.mirrors.length + " mirror(s)"}
+                        </h6>
+                      )}
+                    </td>
+                    <td className="align-middle">{getOriginLabel(d.origin)}</td>
+                    <td className="align-middle">
+                      {d.parents.length == 0
+                        ? "-"
+                        : d.parents.map(getName).join(" ← ")}
+                    </td>
+                    <td className="text-end align-middle">
+                      {formatBytesToGB(d.total_compressed_size)}
+                    </td>
+                    <td className="text-end align-middle">
+                      {formatBytesToGB(d.total_uncompressed_size)}
+                    </td>
+                    <td className="text-end align-middle">
+                      {formatBytesToGB(d.game_cache_size)}
+                    </td>
+                    <td className="text-end align-middle">
+                      {formatBytesToGB(d.offline_cache_size)}
+                    </td>
+                    <td className="text-center align-middle">{d.use_count}</td>
+                  </tr>
+                ))
+              )}
+            </tbody>
+          </table>
+        </div>
+      </Modal.Body>
+      <Modal.Footer>
+        <Button variant="primary" onClick={() => setShow(false)} text="Close" />
+      </Modal.Footer>
+    </Modal>
+  );
+}
@@ -18,15 +18,17 @@ import GameBuildsTab from "@/app/settings/GameBuildsTab";
 import { Tab, Tabs } from "react-bootstrap";
 import LauncherPage from "@/components/LauncherPage";
 import ConfirmationModal from "@/components/ConfirmationModal";
-import { getTheme } from "@/app/util";
+import { getTheme, getErrorMessage } from "@/app/util";
 import AuthenticationTab from "./AuthenticationTab";
 import LauncherSettingsTab from "./LauncherSettingsTab";
 import GameSettingsTab from "./GameSettingsTab";
+import ServerDirectoriesTab from "./ServerDirectoriesTab";
 
 const TAB_LAUNCHER_SETTINGS = "launcher-settings";
 const TAB_GAME_SETTINGS = "game-settings";
 const TAB_GAME_BUILDS = "game-builds";
 const TAB_AUTHENTICATION = "authentication";
+const TAB_SERVER_DIRECTORIES = "server-directories";
 const DEFAULT_TAB = TAB_LAUNCHER_SETTINGS;
 
 export default function SettingsPage() {
@@ -122,7 +124,7 @@ export default function SettingsPage() {
       await syncConfig();
       setInitialFetchDone(true);
     } catch (e) {
-      alertError("Error during init: " + e);
+      alertError("Error during init: " + getErrorMessage(e));
     }
   };
 
@@ -131,7 +133,7 @@ export default function SettingsPage() {
       await invoke("update_config", { config: config });
       alertSuccess("Changes applied successfully");
     } catch (e) {
-      alertError("Error updating config: " + e);
+      alertError("Error updating config: " + getErrorMessage(e));
     }
   };
 
@@ -140,7 +142,7 @@ export default function SettingsPage() {
       await invoke("reset_launcher_config");
       alertSuccess("Launcher settings reset successfully");
     } catch (e) {
-      alertError("Error resetting launcher settings: " + e);
+      alertError("Error resetting launcher settings: " + getErrorMessage(e));
     }
   };
 
@@ -149,7 +151,7 @@ export default function SettingsPage() {
       await invoke("reset_game_config");
       alertSuccess("Game settings reset successfully");
     } catch (e) {
-      alertError("Error resetting game settings: " + e);
+      alertError("Error resetting game settings: " + getErrorMessage(e));
     }
   };
 
@@ -249,6 +251,16 @@ export default function SettingsPage() {
           >
             <AuthenticationTab active={tab == TAB_AUTHENTICATION} />
           </Tab>
+          <Tab
+            eventKey={TAB_SERVER_DIRECTORIES}
+            title={
+              <>
+                <i className="fas fa-globe"></i> <span>Server Directories</span>
+              </>
+            }
+          >
+            <ServerDirectoriesTab active={tab == TAB_SERVER_DIRECTORIES} />
+          </Tab>
         </Tabs>
       </LauncherPage>
       <ConfirmationModal
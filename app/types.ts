@@ -4,6 +4,13 @@ export type ServerEntry = {
   ip?: string;
   version?: string;
   endpoint?: string;
+  folder?: string;
+  managed_by?: string;
+};
+
+export type ServerFolder = {
+  uuid: string;
+  name: string;
 };
 
 export type NewServerDetails = {
@@ -13,9 +20,20 @@ export type NewServerDetails = {
   endpoint?: string;
 };
 
+export type ServerDirectory = {
+  uuid: string;
+  url: string;
+  public_key: string;
+  last_synced?: number;
+  last_error?: string;
+};
+
 export type Servers = {
   servers: ServerEntry[];
   favorites: string[];
+  folders: ServerFolder[];
+  directories: ServerDirectory[];
+  hidden: string[];
 };
 
 export type VersionEntry = {
@@ -43,9 +61,81 @@ export type LauncherSettings = {
   game_cache_path: string;
   offline_cache_path: string;
   proxy_asset_downloads: boolean;
+  address_family: string;
+  manifest_signatures: string;
+  game_cache_quota?: number;
+  max_concurrent_downloads: number;
+  max_concurrent_validations: number;
+  download_speed_limit?: number;
+  background_cache_verification: boolean;
+  cache_verification_interval_hours: number;
   theme?: string;
 };
 
+export type TrustedKey = {
+  name: string;
+  public_key: string;
+  builtin: boolean;
+};
+
+export type VersionOrigin = "builtin" | "app_data" | "endpoint" | "legacy";
+
+export type VersionDetails = {
+  uuid: string;
+  name: string;
+  origin: VersionOrigin;
+  parents: string[];
+  mirrors: string[];
+  total_compressed_size?: number;
+  total_uncompressed_size?: number;
+  game_cache_size: number;
+  offline_cache_size: number;
+  use_count: number;
+};
+
+export type CacheDirKind = "orphan" | "unused" | "unknown";
+
+export type CacheDirEntry = {
+  name: string;
+  offline: boolean;
+  kind: CacheDirKind;
+  version_name?: string;
+  size: number;
+};
+
+export type CacheScanReport = {
+  entries: CacheDirEntry[];
+  reclaimable_bytes: number;
+};
+
+export type CacheCollectResult = {
+  deleted: number;
+  freed_bytes: number;
+  skipped: string[];
+};
+
+export type EvictionEntry = {
+  uuid: string;
+  version_name?: string;
+  size: number;
+  last_used?: number;
+  protected: boolean;
+  evict: boolean;
+};
+
+export type EvictionPlan = {
+  quota?: number;
+  total_bytes: number;
+  evicted_bytes: number;
+  entries: EvictionEntry[];
+};
+
+export type VersionUpdate = {
+  uuid: string;
+  name: string;
+  changes: string[];
+};
+
 export type WindowSize = {
   width: number;
   height: number;
@@ -102,6 +192,11 @@ export type LoginSession = {
   session_token: string;
 };
 
+export type SavedAccounts = {
+  usernames: string[];
+  selected?: string;
+};
+
 export type AccountInfo = {
   username: string;
   email?: string;
@@ -145,6 +240,7 @@ export type VersionCacheProgress = {
   offline: boolean;
   items: Record<string, VersionCacheProgressItem>;
   done: boolean;
+  cancelled: boolean;
 };
 
 export type VersionCacheProgressItem = {
@@ -153,6 +249,19 @@ export type VersionCacheProgressItem = {
   missing: boolean;
 };
 
+export type ValidationReport = {
+  last_validated: number;
+  items: Record<string, VersionCacheProgressItem>;
+};
+
+export type ServerStatus = {
+  uuid: string;
+  online: boolean;
+  player_count?: number;
+  latency_ms?: number;
+  api_version?: string;
+};
+
 export type AlertEvent = {
   variant: string;
   message: string;
@@ -169,3 +278,36 @@ export type SettingsOption = {
   description?: string;
   value?: any;
 };
+
+export type LauncherErrorKind =
+  | "not_logged_in"
+  | "session_expired"
+  | "invalid_credentials"
+  | "server_not_found"
+  | "folder_not_found"
+  | "version_not_found"
+  | "version_not_supported"
+  | "not_endpoint_server"
+  | "login_server_unreachable"
+  | "api_error"
+  | "cache_busy"
+  | "cancelled"
+  | "invalid_signature"
+  | "unsigned_manifest"
+  | "token_store_locked"
+  | "invalid_passphrase"
+  | "io"
+  | "http"
+  | "json"
+  | "other";
+
+export type LauncherError = {
+  kind: LauncherErrorKind;
+  message: string;
+  details?: Record<string, any>;
+};
+
+export type TokenStoreStatus = {
+  key_source: "keyring" | "passphrase";
+  locked: boolean;
+};
@@ -1,4 +1,4 @@
-import { Config, ServerEntry } from "@/app/types";
+import { Config, LauncherError, ServerEntry } from "@/app/types";
 import get_seed from "@/app/seed";
 import { invoke } from "@tauri-apps/api/core";
 
@@ -67,6 +67,15 @@ export function variantToLabel(variant: string) {
   }
 }
 
+export function isLauncherError(e: unknown): e is LauncherError {
+  return typeof e === "object" && e !== null && "kind" in e && "message" in e;
+}
+
+export function getErrorMessage(e: unknown) {
+  if (isLauncherError(e)) return e.message;
+  return String(e);
+}
+
 export function sleep(ms: number) {
   return new Promise((resolve) => setTimeout(resolve, ms));
 }
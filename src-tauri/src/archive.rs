@@ -0,0 +1,164 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufReader, BufWriter, Read as _},
+    path::{Path, PathBuf},
+};
+
+use ffbuildtool::Version;
+use log::*;
+
+use crate::{
+    Result, mirror,
+    signing::SignedManifest,
+    util::{self, ManifestFileInfo},
+};
+
+// layout inside the archive
+const MANIFEST_ENTRY: &str = "manifest.json";
+const SIGNATURE_ENTRY: &str = "manifest.json.sig";
+const MIRRORS_ENTRY: &str = "mirrors.json";
+const FILES_DIR: &str = "files";
+
+/// What's in an offline cache archive besides the cache files
+pub(crate) struct ArchiveManifest {
+    pub original: SignedManifest,
+    pub mirrors: Vec<String>,
+}
+
+/// Writes a version's offline cache and manifest to a tar archive at `dest`,
+/// so it can be side-loaded on a machine with no internet.
+/// The manifest goes in as published, with its signature, so the other side can check it.
+/// Only files the manifest lists go in, so every one of them can be checked on import.
+/// The archive is written next to `dest` and moved into place once it's complete,
+/// so a failed export never leaves a partial archive or clobbers a file already there.
+/// Returns the number of bytes of cache files written.
+pub(crate) fn export_offline_cache(
+    version: &Version,
+    original: &SignedManifest,
+    mirrors: &[String],
+    cache_dir: &Path,
+    dest: &Path,
+) -> Result<u64> {
+    let files = util::get_manifest_file_infos(version);
+    if files.is_empty() {
+        return Err(
+            "This build's manifest doesn't list its files, so its cache can't be checked on import"
+                .into(),
+        );
+    }
+    if !util::is_offline_cache_complete(version, cache_dir) {
+        return Err("Offline cache is incomplete; download or repair it first".into());
+    }
+
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(".part");
+    let partial = PathBuf::from(partial);
+    let result = write_archive(&files, original, mirrors, cache_dir, &partial).and_then(|total| {
+        std::fs::rename(&partial, dest)?;
+        Ok(total)
+    });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+fn write_archive(
+    files: &HashMap<String, ManifestFileInfo>,
+    original: &SignedManifest,
+    mirrors: &[String],
+    cache_dir: &Path,
+    dest: &Path,
+) -> Result<u64> {
+    let mut builder = tar::Builder::new(BufWriter::new(File::create(dest)?));
+    append_bytes(&mut builder, MANIFEST_ENTRY, original.manifest.as_bytes())?;
+    if let Some(signature) = &original.signature {
+        append_bytes(&mut builder, SIGNATURE_ENTRY, signature.as_bytes())?;
+    }
+    // mirrors can be changed locally, and the signed manifest has to stay as it is
+    if !mirrors.is_empty() {
+        append_bytes(&mut builder, MIRRORS_ENTRY, &serde_json::to_vec(mirrors)?)?;
+    }
+
+    let mut total = 0;
+    let mut names: Vec<&String> = files.keys().collect();
+    names.sort();
+    for name in names {
+        builder.append_path_with_name(cache_dir.join(name), Path::new(FILES_DIR).join(name))?;
+        total += files[name].size;
+    }
+    builder
+        .into_inner()?
+        .into_inner()
+        .map_err(|e| e.into_error())?;
+    Ok(total)
+}
+
+fn append_bytes<W: std::io::Write>(
+    builder: &mut tar::Builder<W>,
+    name: &str,
+    data: &[u8],
+) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+/// Unpacks an archive made by `export_offline_cache` into `staging_dir`, returning the manifest
+/// and its signature as they were published. The cache files end up in `staging_dir/files`, unchecked.
+pub(crate) fn unpack_offline_cache(
+    archive_path: &Path,
+    staging_dir: &Path,
+) -> Result<ArchiveManifest> {
+    std::fs::create_dir_all(staging_dir)?;
+    let mut archive = tar::Archive::new(BufReader::new(File::open(archive_path)?));
+    let mut manifest = None;
+    let mut signature = None;
+    let mut mirrors = None;
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.to_path_buf();
+        if path == Path::new(MANIFEST_ENTRY) {
+            let mut json = String::new();
+            entry.read_to_string(&mut json)?;
+            manifest = Some(json);
+        } else if path == Path::new(SIGNATURE_ENTRY) {
+            let mut sig = String::new();
+            entry.read_to_string(&mut sig)?;
+            signature = Some(sig);
+        } else if path == Path::new(MIRRORS_ENTRY) {
+            let mut json = String::new();
+            entry.read_to_string(&mut json)?;
+            mirrors = Some(serde_json::from_str::<Vec<String>>(&json)?);
+        } else if path.starts_with(FILES_DIR) {
+            // unpack_in refuses anything that would land outside the staging directory
+            if !entry.unpack_in(staging_dir)? {
+                warn!(
+                    "Skipping {} in offline cache archive",
+                    path.to_string_lossy()
+                );
+            }
+        } else {
+            debug!(
+                "Ignoring unexpected entry {} in offline cache archive",
+                path.to_string_lossy()
+            );
+        }
+    }
+    let Some(manifest) = manifest else {
+        return Err("Not an offline cache archive: no manifest".into());
+    };
+    let mirrors = mirrors.unwrap_or_else(|| mirror::parse_manifest_mirrors(&manifest));
+    Ok(ArchiveManifest {
+        original: SignedManifest::new(manifest, signature),
+        mirrors,
+    })
+}
+
+pub(crate) fn get_staged_files_dir(staging_dir: &Path) -> PathBuf {
+    staging_dir.join(FILES_DIR)
+}
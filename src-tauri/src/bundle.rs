@@ -0,0 +1,372 @@
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufReader, BufWriter, Read as _, Seek as _, SeekFrom, Write},
+    path::{Component, Path},
+};
+
+use crate::{Error, Result};
+
+const SIGNATURE: &str = "UnityWeb";
+// the header is a handful of fields plus 8 bytes per level
+const MAX_HEADER_SIZE: u64 = 64 * 1024;
+// the listing gets buffered until it can be parsed, so don't let a bad bundle make that unbounded
+const MAX_LISTING_SIZE: usize = 1024 * 1024;
+
+/// Big-endian reader over a bundle header
+struct HeaderReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+impl<'a> HeaderReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or("Unexpected end of bundle")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn read_cstr(&mut self) -> Result<String> {
+        let rest = &self.data[self.pos..];
+        let len = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or("Unterminated string in bundle")?;
+        let s = String::from_utf8_lossy(&rest[..len]).to_string();
+        self.pos += len + 1;
+        Ok(s)
+    }
+}
+
+/// A file listed at the start of a bundle's decompressed stream
+struct BundleEntry {
+    name: String,
+    offset: u64,
+    size: u64,
+}
+
+/// Parses the file listing at the start of the decompressed stream.
+/// Returns None if `data` doesn't hold all of it yet.
+fn parse_listing(data: &[u8]) -> Result<Option<Vec<BundleEntry>>> {
+    let mut listing = HeaderReader::new(data);
+    let parse = |listing: &mut HeaderReader| -> Result<Vec<BundleEntry>> {
+        let file_count = listing.read_u32()?;
+        let mut entries = Vec::new();
+        for _ in 0..file_count {
+            entries.push(BundleEntry {
+                name: listing.read_cstr()?,
+                offset: listing.read_u32()? as u64,
+                size: listing.read_u32()? as u64,
+            });
+        }
+        Ok(entries)
+    };
+    let Ok(mut entries) = parse(&mut listing) else {
+        return Ok(None);
+    };
+
+    // don't let a bad bundle write outside its own directory
+    for entry in &entries {
+        if !Path::new(&entry.name)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return Err(format!("Bad file name {} in bundle", entry.name).into());
+        }
+    }
+    entries.sort_by_key(|entry| entry.offset);
+    Ok(Some(entries))
+}
+
+/// Writes a bundle's files out as its decompressed stream goes by,
+/// so the bundle never has to be held in memory
+struct BundleWriter<'a> {
+    dest: &'a Path,
+    // how far into the decompressed stream we are
+    pos: u64,
+    // the start of the stream, until the listing is all there
+    listing: Vec<u8>,
+    // files still to come, by offset; None until the listing is parsed
+    entries: Option<VecDeque<BundleEntry>>,
+    // the file being written and where in the stream it ends
+    current: Option<(BufWriter<File>, u64, String)>,
+    // why writing stopped, since the decompressor only passes on an io::Error
+    failure: Option<Error>,
+}
+impl<'a> BundleWriter<'a> {
+    fn new(dest: &'a Path) -> Self {
+        Self {
+            dest,
+            pos: 0,
+            listing: Vec::new(),
+            entries: None,
+            current: None,
+            failure: None,
+        }
+    }
+
+    fn consume(&mut self, mut buf: &[u8]) -> Result<()> {
+        if self.entries.is_none() {
+            self.listing.extend_from_slice(buf);
+            let Some(entries) = parse_listing(&self.listing)? else {
+                if self.listing.len() > MAX_LISTING_SIZE {
+                    return Err("Bundle file listing is too large".into());
+                }
+                return Ok(());
+            };
+            std::fs::create_dir_all(self.dest)?;
+            self.entries = Some(entries.into());
+            // the files are found by offset from the start of the stream, listing included
+            let seen = std::mem::take(&mut self.listing);
+            return self.consume(&seen);
+        }
+
+        while !buf.is_empty() {
+            if let Some((file, end, _)) = &mut self.current {
+                let len = (*end - self.pos).min(buf.len() as u64) as usize;
+                file.write_all(&buf[..len])?;
+                buf = &buf[len..];
+                self.pos += len as u64;
+                self.finish_current_if_done()?;
+                continue;
+            }
+
+            let entries = self.entries.as_mut().unwrap();
+            let Some(next) = entries.front() else {
+                // past the last file
+                self.pos += buf.len() as u64;
+                return Ok(());
+            };
+            if next.offset < self.pos {
+                return Err(format!("File {} overlaps another in the bundle", next.name).into());
+            }
+            if next.offset > self.pos {
+                let skip = (next.offset - self.pos).min(buf.len() as u64);
+                buf = &buf[skip as usize..];
+                self.pos += skip;
+                continue;
+            }
+            let next = entries.pop_front().unwrap();
+            let path = self.dest.join(&next.name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = BufWriter::new(File::create(path)?);
+            self.current = Some((file, next.offset + next.size, next.name));
+            self.finish_current_if_done()?;
+        }
+        Ok(())
+    }
+
+    fn finish_current_if_done(&mut self) -> Result<()> {
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|(_, end, _)| *end == self.pos)
+            && let Some((file, _, _)) = self.current.take()
+        {
+            file.into_inner().map_err(|e| e.into_error())?;
+        }
+        Ok(())
+    }
+
+    /// Checks that the stream held everything the listing promised
+    fn finish(self) -> Result<()> {
+        let Some(entries) = self.entries else {
+            return Err("Unexpected end of bundle".into());
+        };
+        let unfinished = self
+            .current
+            .map(|(_, _, name)| name)
+            .or_else(|| entries.front().map(|entry| entry.name.clone()));
+        match unfinished {
+            Some(name) => Err(format!("File {} runs past the end of the bundle", name).into()),
+            None => Ok(()),
+        }
+    }
+}
+impl Write for BundleWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if let Err(e) = self.consume(buf) {
+            let io_error = std::io::Error::other(e.to_string());
+            self.failure = Some(e);
+            return Err(io_error);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Unpacks a UnityWeb asset bundle into `dest`, one file per entry,
+/// which is how the web player lays bundles out in its cache
+pub(crate) fn extract_bundle(bundle_path: &Path, dest: &Path) -> Result<()> {
+    let mut file = File::open(bundle_path)?;
+    let bundle_size = file.metadata()?.len();
+    let mut data = Vec::new();
+    (&mut file).take(MAX_HEADER_SIZE).read_to_end(&mut data)?;
+    let mut header = HeaderReader::new(&data);
+
+    let signature = header.read_cstr()?;
+    if signature != SIGNATURE {
+        return Err(format!("{} is not a UnityWeb bundle", bundle_path.to_string_lossy()).into());
+    }
+    let format = header.read_u32()?;
+    let _player_version = header.read_cstr()?;
+    let _engine_version = header.read_cstr()?;
+    if format >= 4 {
+        // hash and crc
+        header.take(16 + 4)?;
+    }
+    let _minimum_streamed_bytes = header.read_u32()?;
+    let header_size = header.read_u32()? as u64;
+    let _levels_before_streaming = header.read_u32()?;
+    let level_count = header.read_u32()? as usize;
+    if level_count == 0 {
+        return Err("Bundle has no levels".into());
+    }
+    // level ends are cumulative, so the last one covers the whole stream
+    header.take(8 * (level_count - 1))?;
+    let compressed_end = header.read_u32()? as u64;
+
+    if header_size + compressed_end > bundle_size {
+        return Err("Bundle is truncated".into());
+    }
+    file.seek(SeekFrom::Start(header_size))?;
+    let mut compressed = BufReader::new(file.take(compressed_end));
+    let mut writer = BundleWriter::new(dest);
+    if let Err(e) = lzma_rs::lzma_decompress(&mut compressed, &mut writer) {
+        return Err(writer
+            .failure
+            .unwrap_or_else(|| format!("Failed to decompress bundle: {:?}", e).into()));
+    }
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use uuid::Uuid;
+
+    use super::*;
+
+    struct TestDir(PathBuf);
+    impl TestDir {
+        fn new() -> Self {
+            let dir = std::env::temp_dir().join(format!("bundle-test-{}", Uuid::new_v4()));
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn cstr(out: &mut Vec<u8>, s: &str) {
+        out.extend_from_slice(s.as_bytes());
+        out.push(0);
+    }
+
+    /// Lays out a decompressed stream: the listing, then each file's contents in order
+    fn make_stream(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let listing_size: usize = 4 + files
+            .iter()
+            .map(|(name, _)| name.len() + 1 + 8)
+            .sum::<usize>();
+        let mut stream = Vec::new();
+        stream.extend_from_slice(&(files.len() as u32).to_be_bytes());
+        let mut offset = listing_size;
+        for (name, contents) in files {
+            cstr(&mut stream, name);
+            stream.extend_from_slice(&(offset as u32).to_be_bytes());
+            stream.extend_from_slice(&(contents.len() as u32).to_be_bytes());
+            offset += contents.len();
+        }
+        for (_, contents) in files {
+            stream.extend_from_slice(contents);
+        }
+        stream
+    }
+
+    fn make_bundle(stream: &[u8]) -> Vec<u8> {
+        let mut compressed = Vec::new();
+        lzma_rs::lzma_compress(&mut &stream[..], &mut compressed).unwrap();
+
+        let mut fields = Vec::new();
+        cstr(&mut fields, SIGNATURE);
+        fields.extend_from_slice(&3u32.to_be_bytes());
+        cstr(&mut fields, "3.x.x");
+        cstr(&mut fields, "2.5.4b5");
+        fields.extend_from_slice(&0u32.to_be_bytes());
+        // header size, levels before streaming, level count and the one level's ends follow
+        let header_size = fields.len() + 4 * 5;
+        fields.extend_from_slice(&(header_size as u32).to_be_bytes());
+        fields.extend_from_slice(&1u32.to_be_bytes());
+        fields.extend_from_slice(&1u32.to_be_bytes());
+        fields.extend_from_slice(&(compressed.len() as u32).to_be_bytes());
+        fields.extend_from_slice(&(stream.len() as u32).to_be_bytes());
+        fields.extend_from_slice(&compressed);
+        fields
+    }
+
+    fn extract(dir: &TestDir, bundle: &[u8]) -> Result<PathBuf> {
+        let bundle_path = dir.0.join("bundle.unity3d");
+        std::fs::write(&bundle_path, bundle).unwrap();
+        let dest = dir.0.join("out");
+        extract_bundle(&bundle_path, &dest).map(|_| dest)
+    }
+
+    #[test]
+    fn extracts_minimal_bundle() {
+        let dir = TestDir::new();
+        let stream = make_stream(&[("a.txt", b"hello"), ("sub/b.bin", &[1, 2, 3])]);
+        let dest = extract(&dir, &make_bundle(&stream)).unwrap();
+        assert_eq!(std::fs::read(dest.join("a.txt")).unwrap(), b"hello");
+        assert_eq!(std::fs::read(dest.join("sub/b.bin")).unwrap(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn rejects_truncated_header() {
+        let dir = TestDir::new();
+        let bundle = make_bundle(&make_stream(&[("a.txt", b"hello")]));
+        assert!(extract(&dir, &bundle[..SIGNATURE.len() + 3]).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_offset() {
+        let dir = TestDir::new();
+        let mut stream = make_stream(&[("a.txt", b"hello")]);
+        // the offset comes right after the count and the name
+        let offset_pos = 4 + "a.txt".len() + 1;
+        stream[offset_pos..offset_pos + 4].copy_from_slice(&1000u32.to_be_bytes());
+        let err = extract(&dir, &make_bundle(&stream)).unwrap_err();
+        assert!(err.to_string().contains("runs past the end"));
+    }
+
+    #[test]
+    fn rejects_parent_dir_name() {
+        let dir = TestDir::new();
+        let stream = make_stream(&[("../escaped.txt", b"hello")]);
+        let err = extract(&dir, &make_bundle(&stream)).unwrap_err();
+        assert!(err.to_string().contains("Bad file name"));
+        assert!(!dir.0.join("escaped.txt").exists());
+    }
+}
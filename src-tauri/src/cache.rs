@@ -0,0 +1,741 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use ffbuildtool::Version;
+use log::*;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use crate::{
+    CacheProgressItem, Error, GAME_CACHE_OPS, OFFLINE_CACHE_OPS, Result, bundle, proxy,
+    state::{AppState, ServerInfo, get_app_statics},
+    status, util,
+};
+
+const CACHE_USAGE_FILE_NAME: &str = "cache_usage.json";
+const VALIDATION_REPORTS_FILE_NAME: &str = "validation_reports.json";
+
+// working directories that sit in the cache roots next to the caches
+const DOWNLOAD_STAGING_EXTENSION: &str = "download";
+const IMPORT_STAGING_PREFIX: &str = ".import-";
+
+/// Where bundles for a game cache are downloaded to before they're unpacked.
+/// Covered by the version's game cache operation.
+pub(crate) fn get_download_staging_dir(cache_dir: &Path) -> PathBuf {
+    cache_dir.with_extension(DOWNLOAD_STAGING_EXTENSION)
+}
+
+/// Where an offline cache archive is unpacked to before it's checked.
+/// Covered by an offline cache operation for `import_id` for as long as the import runs.
+pub(crate) fn get_import_staging_dir(base_offline_cache_dir: &Path, import_id: Uuid) -> PathBuf {
+    base_offline_cache_dir.join(format!("{}{}", IMPORT_STAGING_PREFIX, import_id))
+}
+
+/// The uuid whose cache operation covers a directory in a cache root:
+/// the version's own for a cache, or the one a staging directory was made for
+fn get_owner_uuid(name: &str) -> Option<Uuid> {
+    let id = name
+        .strip_suffix(DOWNLOAD_STAGING_EXTENSION)
+        .and_then(|name| name.strip_suffix('.'))
+        .or_else(|| name.strip_prefix(IMPORT_STAGING_PREFIX))
+        .unwrap_or(name);
+    Uuid::parse_str(id).ok()
+}
+
+/// Why a cache directory is a candidate for deletion
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheDirKind {
+    /// Named after a version we don't have anymore
+    Orphan,
+    /// Belongs to a version no server uses
+    Unused,
+    /// Not named after a version at all
+    Unknown,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheDirEntry {
+    name: String,
+    offline: bool,
+    kind: CacheDirKind,
+    version_name: Option<String>,
+    size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheScanReport {
+    entries: Vec<CacheDirEntry>,
+    reclaimable_bytes: u64,
+}
+
+/// A directory picked from a `CacheScanReport` for deletion
+#[derive(Debug, Deserialize)]
+pub struct CacheDirTarget {
+    name: String,
+    offline: bool,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct CacheCollectResult {
+    deleted: usize,
+    freed_bytes: u64,
+    /// Directories that were busy, in use again, or failed to delete
+    skipped: Vec<String>,
+}
+
+struct KnownVersion {
+    name: String,
+    used: bool,
+}
+
+/// Snapshot of what the app state knows about versions and cache roots,
+/// so the cache roots can be walked without holding the state lock
+pub struct CacheInventory {
+    game_cache_path: PathBuf,
+    offline_cache_path: PathBuf,
+    versions: HashMap<Uuid, KnownVersion>,
+    /// Versions endpoint servers advertise, which they may pick at launch
+    advertised: HashSet<Uuid>,
+}
+impl CacheInventory {
+    pub fn new(state: &AppState) -> Self {
+        // until every endpoint server has told us which versions it takes,
+        // any of them could be the one it picks
+        let supported_versions = status::get_cached_supported_versions();
+        let mut advertised = HashSet::new();
+        let mut all_known = true;
+        for server in state.servers.get_entries() {
+            if !matches!(server.info, ServerInfo::Endpoint { .. }) {
+                continue;
+            }
+            match supported_versions.get(&server.get_uuid()) {
+                Some(versions) => {
+                    advertised.extend(versions.iter().filter_map(|v| Uuid::parse_str(v).ok()))
+                }
+                None => all_known = false,
+            }
+        }
+
+        let versions = state
+            .versions
+            .get_entries()
+            .iter()
+            .map(|version| {
+                let uuid = version.get_uuid();
+                let known = KnownVersion {
+                    name: util::get_version_name(version),
+                    used: !all_known
+                        || advertised.contains(&uuid)
+                        || state.get_version_use_count(uuid) > 0,
+                };
+                (uuid, known)
+            })
+            .collect();
+        Self {
+            game_cache_path: PathBuf::from(&state.config.launcher.game_cache_path),
+            offline_cache_path: PathBuf::from(&state.config.launcher.offline_cache_path),
+            versions,
+            advertised,
+        }
+    }
+
+    fn get_root(&self, offline: bool) -> &Path {
+        if offline {
+            &self.offline_cache_path
+        } else {
+            &self.game_cache_path
+        }
+    }
+
+    /// Returns None for the caches of versions that are in use
+    fn classify(&self, name: &str) -> Option<(CacheDirKind, Option<String>)> {
+        let Ok(uuid) = Uuid::parse_str(name) else {
+            return Some((CacheDirKind::Unknown, None));
+        };
+        match self.versions.get(&uuid) {
+            // we'd fetch the manifest again the next time the server is picked
+            None if self.advertised.contains(&uuid) => None,
+            None => Some((CacheDirKind::Orphan, None)),
+            Some(known) if !known.used => Some((CacheDirKind::Unused, Some(known.name.clone()))),
+            Some(_) => None,
+        }
+    }
+
+    /// Walks both cache roots for directories that could be deleted. Blocking.
+    pub fn scan(&self) -> CacheScanReport {
+        let mut entries = Vec::new();
+        for offline in [false, true] {
+            let root = self.get_root(offline);
+            let dirs = match std::fs::read_dir(root) {
+                Ok(dirs) => dirs,
+                Err(e) => {
+                    debug!("Skipping cache root {}: {}", root.to_string_lossy(), e);
+                    continue;
+                }
+            };
+            for dir in dirs.flatten() {
+                if !dir.file_type().is_ok_and(|t| t.is_dir()) {
+                    continue;
+                }
+                let name = dir.file_name().to_string_lossy().to_string();
+                let Some((kind, version_name)) = self.classify(&name) else {
+                    continue;
+                };
+                entries.push(CacheDirEntry {
+                    size: util::get_dir_size_or_zero(&dir.path()),
+                    name,
+                    offline,
+                    kind,
+                    version_name,
+                });
+            }
+        }
+
+        let reclaimable_bytes = entries.iter().map(|e| e.size).sum();
+        CacheScanReport {
+            entries,
+            reclaimable_bytes,
+        }
+    }
+
+    /// Deletes the given cache directories, skipping any that are being worked on
+    /// or that stopped being deletable since they were scanned
+    pub async fn collect(
+        &self,
+        app_state: &Mutex<AppState>,
+        targets: Vec<CacheDirTarget>,
+    ) -> CacheCollectResult {
+        let mut result = CacheCollectResult::default();
+        for target in targets {
+            let label = format!(
+                "{} ({})",
+                target.name,
+                if target.offline { "offline" } else { "game" }
+            );
+            match self.collect_one(app_state, &target).await {
+                Ok(Some(freed)) => {
+                    info!("Deleted cache directory {}", label);
+                    result.deleted += 1;
+                    result.freed_bytes += freed;
+                }
+                Ok(None) => result.skipped.push(label),
+                Err(e) => {
+                    warn!("Failed to delete cache directory {}: {}", label, e);
+                    result.skipped.push(label);
+                }
+            }
+        }
+        result
+    }
+
+    async fn collect_one(
+        &self,
+        app_state: &Mutex<AppState>,
+        target: &CacheDirTarget,
+    ) -> Result<Option<u64>> {
+        // only ever delete direct children of the cache root
+        let name = target.name.as_str();
+        if name.is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+            return Err(format!("Invalid cache directory name {}", name).into());
+        }
+        if self.classify(name).is_none() {
+            debug!("Cache directory {} is in use; not deleting", name);
+            return Ok(None);
+        }
+        let path = self.get_root(target.offline).join(name);
+        if !path.is_dir() {
+            return Ok(None);
+        }
+
+        let Some(uuid) = get_owner_uuid(name) else {
+            return delete_dir_blocking(path).await.map(Some);
+        };
+
+        // hold the op lock so nothing downloads into, validates or unpacks to the directory
+        // as we delete it
+        if begin_op(uuid, target.offline).await.is_err() {
+            debug!("Cache for {} is busy; not deleting", uuid);
+            return Ok(None);
+        }
+        let result = delete_dir_blocking(path).await;
+        if name == uuid.to_string() {
+            end_op_discarding_report(app_state, uuid, target.offline).await;
+        } else {
+            // staging directories don't have reports of their own
+            end_op(uuid, target.offline).await;
+        }
+        result.map(Some)
+    }
+}
+
+/// Measures and then deletes a directory on the blocking pool, returning how many bytes it held.
+async fn delete_dir_blocking(path: PathBuf) -> Result<u64> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let size = util::get_dir_size_or_zero(&path);
+        util::delete_dir(&path).map(|_| size)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// When each version's game cache was last launched, for picking what to evict
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CacheUsage {
+    last_used: HashMap<Uuid, u64>,
+}
+impl CacheUsage {
+    pub fn load() -> Self {
+        match Self::load_internal() {
+            Ok(usage) => usage,
+            Err(e) => {
+                warn!("Failed to load cache usage: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    fn load_internal() -> Result<Self> {
+        let path = get_app_statics().app_data_dir.join(CACHE_USAGE_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let usage_str = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&usage_str)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = get_app_statics().app_data_dir.join(CACHE_USAGE_FILE_NAME);
+        let usage_str = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, usage_str)?;
+        Ok(())
+    }
+
+    pub fn touch(&mut self, uuid: Uuid) {
+        self.last_used.insert(uuid, util::get_timestamp());
+    }
+
+    pub fn get_last_used(&self, uuid: Uuid) -> Option<u64> {
+        self.last_used.get(&uuid).copied()
+    }
+}
+
+/// What the last full pass over a cache found, file by file
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ValidationReport {
+    last_validated: u64,
+    items: HashMap<String, CacheProgressItem>,
+}
+impl ValidationReport {
+    pub fn new(items: HashMap<String, CacheProgressItem>) -> Self {
+        Self {
+            last_validated: util::get_timestamp(),
+            items,
+        }
+    }
+
+    pub fn get_last_validated(&self) -> u64 {
+        self.last_validated
+    }
+
+    pub fn get_items(&self) -> &HashMap<String, CacheProgressItem> {
+        &self.items
+    }
+
+    /// The files a repair has to fix, corrupt or missing
+    pub fn get_flagged(&self) -> Vec<String> {
+        let mut flagged: Vec<String> = self
+            .items
+            .iter()
+            .filter(|(_, item)| item.corrupt)
+            .map(|(name, _)| name.clone())
+            .collect();
+        flagged.sort();
+        flagged
+    }
+}
+
+/// The last validation report for each version's game and offline caches
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ValidationReports {
+    game: HashMap<Uuid, ValidationReport>,
+    offline: HashMap<Uuid, ValidationReport>,
+}
+impl ValidationReports {
+    pub fn load() -> Self {
+        match Self::load_internal() {
+            Ok(reports) => reports,
+            Err(e) => {
+                warn!("Failed to load validation reports: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    fn load_internal() -> Result<Self> {
+        let path = get_app_statics()
+            .app_data_dir
+            .join(VALIDATION_REPORTS_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let reports_str = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&reports_str)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = get_app_statics()
+            .app_data_dir
+            .join(VALIDATION_REPORTS_FILE_NAME);
+        let reports_str = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, reports_str)?;
+        Ok(())
+    }
+
+    fn get_map(&mut self, offline: bool) -> &mut HashMap<Uuid, ValidationReport> {
+        if offline {
+            &mut self.offline
+        } else {
+            &mut self.game
+        }
+    }
+
+    pub fn get(&self, uuid: Uuid, offline: bool) -> Option<&ValidationReport> {
+        if offline {
+            self.offline.get(&uuid)
+        } else {
+            self.game.get(&uuid)
+        }
+    }
+
+    pub fn set(&mut self, uuid: Uuid, offline: bool, report: ValidationReport) {
+        self.get_map(offline).insert(uuid, report);
+    }
+
+    /// Returns whether there was a report to remove
+    pub fn remove(&mut self, uuid: Uuid, offline: bool) -> bool {
+        self.get_map(offline).remove(&uuid).is_some()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct EvictionEntry {
+    uuid: Uuid,
+    version_name: Option<String>,
+    size: u64,
+    last_used: Option<u64>,
+    /// Needed by the selected server, so never evicted
+    protected: bool,
+    evict: bool,
+}
+
+/// What enforcing the game cache quota would delete
+#[derive(Debug, Serialize)]
+pub struct EvictionPlan {
+    #[serde(skip)]
+    game_cache_path: PathBuf,
+    quota: Option<u64>,
+    total_bytes: u64,
+    evicted_bytes: u64,
+    /// Least recently used first
+    entries: Vec<EvictionEntry>,
+}
+/// Snapshot of what working out an `EvictionPlan` needs from the app state,
+/// so the game caches can be measured without holding the state lock
+pub struct EvictionPlanner {
+    quota: Option<u64>,
+    game_cache_path: PathBuf,
+    version_names: HashMap<Uuid, String>,
+    cache_usage: CacheUsage,
+}
+impl EvictionPlanner {
+    pub fn new(state: &AppState) -> Self {
+        Self {
+            quota: state.config.launcher.game_cache_quota,
+            game_cache_path: PathBuf::from(&state.config.launcher.game_cache_path),
+            version_names: state
+                .versions
+                .get_entries()
+                .iter()
+                .map(|version| (version.get_uuid(), util::get_version_name(version)))
+                .collect(),
+            cache_usage: state.cache_usage.clone(),
+        }
+    }
+
+    /// Works out which game caches to evict, least recently used first, to get under the quota.
+    /// Caches for the `protected` versions are kept even if that leaves us over. Blocking.
+    pub fn plan(&self, protected: &HashSet<Uuid>) -> EvictionPlan {
+        let quota = self.quota;
+        let mut entries = Vec::new();
+        if let Ok(dirs) = std::fs::read_dir(&self.game_cache_path) {
+            for dir in dirs.flatten() {
+                if !dir.file_type().is_ok_and(|t| t.is_dir()) {
+                    continue;
+                }
+                // only version caches count; anything else isn't ours to evict
+                let Ok(uuid) = Uuid::parse_str(&dir.file_name().to_string_lossy()) else {
+                    continue;
+                };
+                entries.push(EvictionEntry {
+                    uuid,
+                    version_name: self.version_names.get(&uuid).cloned(),
+                    size: util::get_dir_size_or_zero(&dir.path()),
+                    last_used: self.cache_usage.get_last_used(uuid),
+                    protected: protected.contains(&uuid),
+                    evict: false,
+                });
+            }
+        }
+        // never-used caches predate tracking, so they go first
+        entries.sort_by_key(|e| e.last_used.unwrap_or(0));
+
+        let total_bytes = entries.iter().map(|e| e.size).sum();
+        let mut remaining: u64 = total_bytes;
+        if let Some(quota) = quota {
+            for entry in entries.iter_mut() {
+                if remaining <= quota {
+                    break;
+                }
+                if entry.protected {
+                    continue;
+                }
+                entry.evict = true;
+                remaining -= entry.size;
+            }
+        }
+
+        EvictionPlan {
+            game_cache_path: self.game_cache_path.clone(),
+            quota,
+            total_bytes,
+            evicted_bytes: total_bytes - remaining,
+            entries,
+        }
+    }
+}
+
+impl EvictionPlan {
+    pub fn is_empty(&self) -> bool {
+        !self.entries.iter().any(|e| e.evict)
+    }
+
+    /// Deletes the caches marked for eviction, skipping busy ones. Returns the bytes freed.
+    pub async fn execute(&self, app_state: &Mutex<AppState>) -> u64 {
+        let mut freed = 0;
+        for entry in self.entries.iter().filter(|e| e.evict) {
+            if begin_op(entry.uuid, false).await.is_err() {
+                debug!("Game cache for {} is busy; not evicting", entry.uuid);
+                continue;
+            }
+            let path = self.game_cache_path.join(entry.uuid.to_string());
+            match delete_dir_blocking(path).await {
+                Ok(_) => {
+                    info!(
+                        "Evicted game cache for {} ({} bytes)",
+                        entry
+                            .version_name
+                            .as_deref()
+                            .unwrap_or(&entry.uuid.to_string()),
+                        entry.size
+                    );
+                    freed += entry.size;
+                }
+                Err(e) => warn!("Failed to evict game cache for {}: {}", entry.uuid, e),
+            }
+            end_op_discarding_report(app_state, entry.uuid, false).await;
+        }
+        freed
+    }
+}
+
+/// Fills a version's game cache ahead of launch by unpacking its bundles like the game would.
+/// Bundles the offline cache has in full are unpacked from there, and the rest get downloaded
+/// to a staging directory next to the game cache.
+/// With `only_bundles`, just those get unpacked again.
+/// Bundles that fail to unpack are logged and skipped, so the caller should validate the result
+/// against the manifest's uncompressed hashes.
+pub(crate) async fn unpack_game_cache(
+    version: &Version,
+    cache_dir: &Path,
+    offline_cache_dir: &Path,
+    mirrors: &[String],
+    only_bundles: Option<&[String]>,
+    token: &CancellationToken,
+) -> Result<()> {
+    let bundle_names = match only_bundles {
+        Some(names) => names.to_vec(),
+        None => util::get_manifest_bundle_names(version),
+    };
+    if bundle_names.is_empty() {
+        return Err(
+            "This build's manifest doesn't list its bundles; launch it to fill the cache instead"
+                .into(),
+        );
+    }
+
+    let files = util::get_manifest_file_infos(version);
+    let (offline_names, missing): (Vec<String>, Vec<String>) = bundle_names
+        .iter()
+        .cloned()
+        .partition(|name| util::is_offline_file_complete(&files, offline_cache_dir, name));
+
+    let staging_dir = get_download_staging_dir(cache_dir);
+    if missing.is_empty() {
+        debug!(
+            "Unpacking game cache from {}",
+            offline_cache_dir.to_string_lossy()
+        );
+    } else if only_bundles.is_some() || !offline_names.is_empty() {
+        debug!(
+            "Downloading {} bundle(s) missing from {}",
+            missing.len(),
+            offline_cache_dir.to_string_lossy()
+        );
+        std::fs::create_dir_all(&staging_dir)?;
+        let download = proxy::repair_files(version, mirrors, &staging_dir, &missing, |_, _| {});
+        tokio::select! {
+            _ = token.cancelled() => return Err(Error::Cancelled),
+            _ = download => {}
+        }
+    } else {
+        // staging left behind by an interrupted download is repaired rather than started over,
+        // so only the bundles that didn't finish get fetched again, each from the start
+        let resume = !util::is_dir_empty(&staging_dir).unwrap_or(true);
+        std::fs::create_dir_all(&staging_dir)?;
+        let staging = staging_dir.to_string_lossy().to_string();
+        let (version, _proxy) = proxy::proxy_version(version, mirrors).await?;
+        let download = async {
+            if resume {
+                debug!("Resuming bundle download into {}", staging);
+                version.repair(&staging, None).await.map(|_| ())
+            } else {
+                version.download_compressed(&staging, None).await
+            }
+        };
+        tokio::select! {
+            _ = token.cancelled() => return Err(Error::Cancelled),
+            res = download => {
+                res.map_err(|e| format!("Failed to download bundles: {:?}", e))?;
+            }
+        }
+    }
+
+    // decompressing is CPU-bound, so keep it off the async runtime
+    let dest_dir = cache_dir.to_path_buf();
+    let offline_dir = offline_cache_dir.to_path_buf();
+    let download_dir = staging_dir.clone();
+    let extract_token = token.clone();
+    let unpacked = tauri::async_runtime::spawn_blocking(move || {
+        for name in bundle_names {
+            if extract_token.is_cancelled() {
+                return;
+            }
+            let source_dir = match offline_names.contains(&name) {
+                true => &offline_dir,
+                false => &download_dir,
+            };
+            let bundle_path = source_dir.join(&name);
+            if let Err(e) = bundle::extract_bundle(&bundle_path, &dest_dir.join(&name)) {
+                warn!("Failed to unpack {}: {}", name, e);
+            }
+        }
+    })
+    .await;
+    unpacked.map_err(|e| e.to_string())?;
+    if token.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
+
+    if !missing.is_empty()
+        && let Err(e) = util::delete_dir(&staging_dir)
+    {
+        warn!(
+            "Failed to clean up {}: {}",
+            staging_dir.to_string_lossy(),
+            e
+        );
+    }
+    Ok(())
+}
+
+fn get_ops(offline: bool) -> &'static Mutex<HashMap<Uuid, CancellationToken>> {
+    let ops = if offline {
+        &OFFLINE_CACHE_OPS
+    } else {
+        &GAME_CACHE_OPS
+    };
+    ops.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Marks a version's cache as busy until `end_op` is called.
+/// The returned token is how `cancel_op` asks the operation to stop.
+pub(crate) async fn begin_op(uuid: Uuid, offline: bool) -> Result<CancellationToken> {
+    let mut ops = get_ops(offline).lock().await;
+    if ops.contains_key(&uuid) {
+        return Err(Error::CacheBusy);
+    }
+    let token = CancellationToken::new();
+    ops.insert(uuid, token.clone());
+    Ok(token)
+}
+
+/// Runs `f` unless an operation holds the version's cache. Doesn't hold the cache itself,
+/// so any number of these can run at once; `f` should be quick, like a rename.
+pub(crate) async fn unless_op_running<T>(
+    uuid: Uuid,
+    offline: bool,
+    f: impl FnOnce() -> T,
+) -> Result<T> {
+    // holding the map keeps an operation from starting until `f` is done
+    let ops = get_ops(offline).lock().await;
+    if ops.contains_key(&uuid) {
+        return Err(Error::CacheBusy);
+    }
+    Ok(f())
+}
+
+/// Whether anything is downloading, validating, repairing or deleting a cache right now
+pub(crate) async fn is_any_op_running() -> bool {
+    !get_ops(false).lock().await.is_empty() || !get_ops(true).lock().await.is_empty()
+}
+
+pub(crate) async fn end_op(uuid: Uuid, offline: bool) {
+    get_ops(offline).lock().await.remove(&uuid);
+}
+
+/// Like `end_op`, for operations that deleted or replaced the cache directory,
+/// whose validation report then no longer describes what's on disk.
+pub(crate) async fn end_op_discarding_report(
+    app_state: &Mutex<AppState>,
+    uuid: Uuid,
+    offline: bool,
+) {
+    discard_validation_report(&mut *app_state.lock().await, uuid, offline);
+    end_op(uuid, offline).await;
+}
+
+/// Forgets a cache's validation report once its directory is gone or replaced.
+pub(crate) fn discard_validation_report(state: &mut AppState, uuid: Uuid, offline: bool) {
+    if state.validation_reports.remove(uuid, offline)
+        && let Err(e) = state.validation_reports.save()
+    {
+        warn!("Failed to save validation reports: {}", e);
+    }
+}
+
+/// Returns whether there was an operation to cancel. The operation only
+/// stops at its next await point, so the cache stays busy until it does.
+pub(crate) async fn cancel_op(uuid: Uuid, offline: bool) -> bool {
+    match get_ops(offline).lock().await.get(&uuid) {
+        Some(token) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,185 @@
+use log::*;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    Error, Result, endpoint, launch,
+    state::{self, AppState, Server, ServerInfo, VersionOrigin},
+    util::{self, AlertSink},
+};
+
+/// Unlocks passphrase-protected saved logins when there's no OS keyring
+const TOKEN_PASSPHRASE_ENV_VAR: &str = "OPENFUSION_TOKEN_PASSPHRASE";
+
+const USAGE: &str = "Usage: openfusionlauncher launch --server <uuid|name> [--version <uuid>] [--account <username>]";
+
+struct LaunchArgs {
+    server: String,
+    version: Option<Uuid>,
+    account: Option<String>,
+}
+impl LaunchArgs {
+    fn parse(args: &[String]) -> Result<Self> {
+        let mut server = None;
+        let mut version = None;
+        let mut account = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--server" | "-s" => {
+                    server = Some(args.next().ok_or("Missing value for --server")?.clone());
+                }
+                "--version" | "-v" => {
+                    let uuid = args.next().ok_or("Missing value for --version")?;
+                    version = Some(Uuid::parse_str(uuid)?);
+                }
+                "--account" | "-a" => {
+                    account = Some(args.next().ok_or("Missing value for --account")?.clone());
+                }
+                other => return Err(format!("Unknown argument: {}", other).into()),
+            }
+        }
+
+        Ok(Self {
+            server: server.ok_or("Missing --server")?,
+            version,
+            account,
+        })
+    }
+}
+
+/// Entry point for `openfusionlauncher launch ...`.
+/// Runs the same pipeline as `prep_launch` + `do_launch` without opening a window,
+/// printing alerts to stderr. Returns the process exit code.
+pub(crate) fn run(context: &tauri::Context, args: &[String]) -> i32 {
+    let args = match LaunchArgs::parse(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            return 2;
+        }
+    };
+
+    state::init_app_statics_headless(context);
+    match tauri::async_runtime::block_on(launch(args)) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("Failed to launch: {}", e);
+            1
+        }
+    }
+}
+
+async fn launch(args: LaunchArgs) -> Result<i32> {
+    let alerts = AlertSink::Stderr;
+    let app_state = Mutex::new(AppState::load(&alerts));
+    let mut state = app_state.lock().await;
+
+    let server = state
+        .servers
+        .find_entry(&args.server)
+        .ok_or(format!("Server {} not found", args.server))?
+        .clone();
+    let server_uuid = server.get_uuid();
+
+    let version_uuid = match args.version {
+        Some(uuid) => uuid,
+        None => get_default_version(&server).await?,
+    };
+
+    let session_token = match &server.info {
+        ServerInfo::Simple { .. } => None,
+        ServerInfo::Endpoint { endpoint, .. } => {
+            ensure_version_fetched(&mut state, endpoint, version_uuid).await?;
+
+            if state.tokens.is_locked() {
+                let passphrase =
+                    std::env::var(TOKEN_PASSPHRASE_ENV_VAR).map_err(|_| Error::TokenStoreLocked)?;
+                state.tokens.unlock(&passphrase)?;
+            }
+
+            let Some(account) = state.tokens.get_token(server_uuid, args.account.as_deref()) else {
+                return Err(
+                    "Not logged in. Log in from the launcher with \"Remember me\" checked first."
+                        .into(),
+                );
+            };
+            let refresh_token = account.get_refresh_token();
+            if refresh_token.is_expired() {
+                return Err(Error::SessionExpired);
+            }
+            let session = endpoint::get_session(refresh_token.get_token(), endpoint).await?;
+            Some(session.get_session_token().to_string())
+        }
+    };
+
+    drop(state);
+    launch::prep_launch(
+        &app_state,
+        &alerts,
+        server_uuid,
+        version_uuid,
+        session_token,
+    )
+    .await?;
+    let mut state = app_state.lock().await;
+
+    let mut cmd = state.launch_cmd.take().ok_or("No launch prepared")?;
+    let mut proc = launch::spawn_game(&mut cmd)?;
+    eprintln!("Game launched (pid {})", proc.id());
+    let exit_result = proc.wait();
+
+    // shutdown the asset proxy
+    if let Some(proxy) = state.proxy.take() {
+        proxy.abort();
+    }
+
+    let exit_code = exit_result?;
+    Ok(exit_code.code().unwrap_or(0))
+}
+
+async fn get_default_version(server: &Server) -> Result<Uuid> {
+    let version = match &server.info {
+        ServerInfo::Simple { version, .. } => version.clone(),
+        ServerInfo::Endpoint {
+            endpoint,
+            preferred_version,
+        } => match preferred_version {
+            Some(version) => version.clone(),
+            None => {
+                let info = endpoint::get_info(endpoint).await?;
+                info.get_supported_versions()
+                    .into_iter()
+                    .next()
+                    .ok_or("Server does not advertise any versions")?
+            }
+        },
+    };
+    Ok(Uuid::parse_str(&version)?)
+}
+
+async fn ensure_version_fetched(
+    state: &mut AppState,
+    endpoint: &str,
+    version_uuid: Uuid,
+) -> Result<()> {
+    if state.versions.get_entry(version_uuid).is_some() {
+        return Ok(());
+    }
+
+    debug!("Fetching version {} from {}", version_uuid, endpoint);
+    let verifier = state.get_manifest_verifier();
+    let fetched = endpoint::fetch_version(endpoint, version_uuid, &verifier).await?;
+    if let Some(warning) = fetched.warning {
+        eprintln!("[warning] {}", warning);
+    }
+    let imported = util::import_versions(vec![fetched.version])?;
+    for version in imported {
+        fetched.original.save(version.get_uuid())?;
+        state
+            .versions
+            .set_mirrors(version.get_uuid(), fetched.mirrors.clone());
+        state.versions.add_entry(version, VersionOrigin::Endpoint);
+    }
+    Ok(())
+}
@@ -1,7 +1,11 @@
-use crate::util;
+use crate::{signing::SignaturePolicy, util};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+pub const DEFAULT_MAX_CONCURRENT_DOWNLOADS: usize = 1;
+pub const DEFAULT_MAX_CONCURRENT_VALIDATIONS: usize = 2; // compromise. 1 is a lot for an HDD, but SSDs can handle 3-5
+pub const DEFAULT_CACHE_VERIFICATION_INTERVAL_HOURS: u64 = 24;
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "snake_case")]
 pub enum LauncherTheme {
@@ -18,6 +22,17 @@ pub enum LaunchBehavior {
     StayOpen,
 }
 
+/// Which kind of address to use when a server hostname resolves to both
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFamily {
+    #[default]
+    PreferIpv4,
+    PreferIpv6,
+    Ipv4Only,
+    Ipv6Only,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct LauncherSettings {
     #[serde(default = "util::true_fn")]
@@ -47,6 +62,32 @@ pub struct LauncherSettings {
 
     #[serde(default = "util::true_fn")]
     pub proxy_asset_downloads: bool,
+
+    #[serde(default)]
+    pub address_family: AddressFamily,
+
+    #[serde(default)]
+    pub manifest_signatures: SignaturePolicy,
+
+    // in bytes; none = unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_cache_quota: Option<u64>,
+
+    #[serde(default = "default_max_concurrent_downloads")]
+    pub max_concurrent_downloads: usize,
+
+    #[serde(default = "default_max_concurrent_validations")]
+    pub max_concurrent_validations: usize,
+
+    // in bytes per second; none = unlimited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub download_speed_limit: Option<u64>,
+
+    #[serde(default = "util::false_fn")]
+    pub background_cache_verification: bool,
+
+    #[serde(default = "default_cache_verification_interval_hours")]
+    pub cache_verification_interval_hours: u64,
 }
 impl Default for LauncherSettings {
     fn default() -> Self {
@@ -60,10 +101,30 @@ impl Default for LauncherSettings {
             game_cache_path: util::get_default_cache_dir(),
             offline_cache_path: util::get_default_offline_cache_dir(),
             proxy_asset_downloads: true,
+            address_family: AddressFamily::PreferIpv4,
+            manifest_signatures: SignaturePolicy::Warn,
+            game_cache_quota: None,
+            max_concurrent_downloads: DEFAULT_MAX_CONCURRENT_DOWNLOADS,
+            max_concurrent_validations: DEFAULT_MAX_CONCURRENT_VALIDATIONS,
+            download_speed_limit: None,
+            background_cache_verification: false,
+            cache_verification_interval_hours: DEFAULT_CACHE_VERIFICATION_INTERVAL_HOURS,
         }
     }
 }
 
+fn default_max_concurrent_downloads() -> usize {
+    DEFAULT_MAX_CONCURRENT_DOWNLOADS
+}
+
+fn default_max_concurrent_validations() -> usize {
+    DEFAULT_MAX_CONCURRENT_VALIDATIONS
+}
+
+fn default_cache_verification_interval_hours() -> u64 {
+    DEFAULT_CACHE_VERIFICATION_INTERVAL_HOURS
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Copy, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum GraphicsApi {
@@ -0,0 +1,144 @@
+use std::sync::{
+    Mutex,
+    atomic::{AtomicBool, Ordering},
+};
+
+use log::*;
+use reqwest::Url;
+use tauri::{Emitter as _, Manager as _};
+
+use crate::{
+    ADD_SERVER_REQUESTED_EVENT, NewServerDetails, Result, endpoint,
+    state::AppState,
+    util::{self, AlertVariant},
+};
+
+const DEEP_LINK_SCHEME: &str = "openfusion";
+
+/// Links that arrived before the app state was loaded
+static PENDING_LINKS: Mutex<Vec<Url>> = Mutex::new(Vec::new());
+static STATE_READY: AtomicBool = AtomicBool::new(false);
+
+/// Queues up `openfusion://` links to be handled. They're handled right away
+/// unless the app state hasn't been loaded yet, in which case `set_ready` picks them up.
+pub(crate) fn open_urls(app_handle: &tauri::AppHandle, urls: Vec<Url>) {
+    PENDING_LINKS.lock().unwrap().extend(urls);
+    if STATE_READY.load(Ordering::SeqCst) {
+        handle_pending(app_handle.clone());
+    }
+}
+
+/// Called once the app state is loaded
+pub(crate) fn set_ready(app_handle: &tauri::AppHandle) {
+    STATE_READY.store(true, Ordering::SeqCst);
+    handle_pending(app_handle.clone());
+}
+
+fn handle_pending(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let urls = std::mem::take(&mut *PENDING_LINKS.lock().unwrap());
+        for url in urls {
+            info!("Opening link {}", url);
+            if let Err(e) = handle_url(&app_handle, &url).await {
+                warn!("Failed to open link {}: {}", url, e);
+                util::send_alert(
+                    app_handle.clone(),
+                    AlertVariant::Error,
+                    &format!("Failed to open link: {}", e),
+                );
+            }
+        }
+    });
+}
+
+async fn handle_url(app_handle: &tauri::AppHandle, url: &Url) -> Result<()> {
+    if url.scheme() != DEEP_LINK_SCHEME {
+        return Err(format!("Unsupported link scheme {}", url.scheme()).into());
+    }
+
+    match url.host_str() {
+        Some("add-server") => add_server(app_handle, url).await,
+        _ => Err(format!("Unsupported link {}", url).into()),
+    }
+}
+
+/// `openfusion://add-server?endpoint=<host>[&name=<description>]`.
+/// Anyone can make such a link, so the frontend has the user confirm before the server is added.
+async fn add_server(app_handle: &tauri::AppHandle, url: &Url) -> Result<()> {
+    let mut endpoint_host = None;
+    let mut name = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "endpoint" => endpoint_host = Some(value.trim().to_string()),
+            "name" => name = Some(value.trim().to_string()),
+            _ => {}
+        }
+    }
+
+    let endpoint_host = endpoint_host.ok_or("Link is missing the endpoint")?;
+    // endpoint servers are only ever reached over HTTPS
+    let endpoint_host = match endpoint_host.split_once("://") {
+        Some((scheme, rest)) if scheme.eq_ignore_ascii_case("https") => rest,
+        Some((scheme, _)) => {
+            return Err(format!("Unsupported endpoint scheme {}", scheme).into());
+        }
+        None => endpoint_host.as_str(),
+    };
+    let endpoint_host = endpoint_host.trim_end_matches('/').to_string();
+    if !is_valid_endpoint(&endpoint_host) {
+        return Err(format!("Invalid endpoint {}", endpoint_host).into());
+    }
+
+    let state = app_handle.state::<tokio::sync::Mutex<AppState>>();
+    if let Some(existing) = state
+        .lock()
+        .await
+        .servers
+        .get_entry_by_endpoint(&endpoint_host)
+    {
+        util::send_alert(
+            app_handle.clone(),
+            AlertVariant::Info,
+            &format!(
+                "{} is already in your server list",
+                existing.get_description().unwrap_or(endpoint_host)
+            ),
+        );
+        return Ok(());
+    }
+
+    // fall back to the name the server gives itself
+    let description = match name.filter(|n| !n.is_empty()) {
+        Some(name) => name,
+        None => match endpoint::get_info(&endpoint_host).await {
+            Ok(info) => info.server_name,
+            Err(e) => {
+                warn!("Couldn't fetch info for {}: {}", endpoint_host, e);
+                endpoint_host.clone()
+            }
+        },
+    };
+
+    let details = NewServerDetails {
+        description,
+        ip: None,
+        version: None,
+        endpoint: Some(endpoint_host),
+    };
+    app_handle
+        .emit(ADD_SERVER_REQUESTED_EVENT, details)
+        .map_err(|e| format!("Failed to ask to add the server: {}", e))?;
+    Ok(())
+}
+
+/// Same rules as the endpoint field in the server editor: a host and an optional path
+fn is_valid_endpoint(endpoint_host: &str) -> bool {
+    let Ok(url) = Url::parse(&format!("https://{}", endpoint_host)) else {
+        return false;
+    };
+    url.host_str().is_some()
+        && url.username().is_empty()
+        && url.password().is_none()
+        && url.query().is_none()
+        && url.fragment().is_none()
+}
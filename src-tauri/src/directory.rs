@@ -0,0 +1,87 @@
+use std::{
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
+
+use log::*;
+use serde::Deserialize;
+use tauri::{Emitter as _, Manager as _};
+
+use crate::{
+    Result, SERVERS_CHANGED_EVENT, signing,
+    state::{AppState, FlatServer, ServerDirectory},
+    status, util,
+};
+
+const SYNC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+static SYNC_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// What a server directory serves. A detached signature over the raw JSON
+/// is expected at the same URL with `.sig` appended.
+#[derive(Debug, Deserialize)]
+struct DirectoryList {
+    servers: Vec<FlatServer>,
+}
+
+/// Spawns the background task that keeps directory entries up to date.
+/// Only the first call does anything, since the state has to be loaded before we sync.
+pub(crate) fn start_syncing(app_handle: tauri::AppHandle) {
+    if SYNC_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        loop {
+            sync_all(&app_handle).await;
+            tokio::time::sleep(SYNC_INTERVAL).await;
+        }
+    });
+}
+
+/// Fetches every subscribed directory and applies the results to the server list
+pub(crate) async fn sync_all(app_handle: &tauri::AppHandle) {
+    let state = app_handle.state::<tokio::sync::Mutex<AppState>>();
+    let directories: Vec<ServerDirectory> = state.lock().await.servers.get_directories().to_vec();
+    if directories.is_empty() {
+        return;
+    }
+
+    // don't hold the state lock over the network
+    let mut results = Vec::with_capacity(directories.len());
+    for directory in &directories {
+        let result = fetch_list(directory.get_url(), directory.get_public_key()).await;
+        results.push((directory.get_uuid(), result));
+    }
+
+    let mut state = state.lock().await;
+    let mut changed = false;
+    for (uuid, result) in results {
+        match result.and_then(|entries| state.servers.apply_directory(uuid, entries)) {
+            Ok(c) => changed |= c,
+            Err(e) => {
+                warn!("Failed to sync server directory {}: {}", uuid, e);
+                state.servers.set_directory_error(uuid, &e.to_string());
+            }
+        }
+    }
+    state.save();
+    drop(state);
+
+    if changed {
+        info!("Server directories updated the server list");
+        if let Err(e) = app_handle.emit(SERVERS_CHANGED_EVENT, ()) {
+            error!("Failed to emit servers changed event: {}", e);
+        }
+        status::request_poll(app_handle.clone());
+    }
+}
+
+/// Downloads a directory's server list and checks its signature
+pub(crate) async fn fetch_list(url: &str, public_key: &str) -> Result<Vec<FlatServer>> {
+    let public_key = signing::parse_public_key(public_key)?;
+    let list_str = util::do_simple_get(url).await?;
+    let signature = util::do_simple_get(&signing::get_signature_url(url)).await?;
+    signing::verify_detached(list_str.as_bytes(), &signature, &public_key, url)?;
+    let list: DirectoryList = serde_json::from_str(&list_str)?;
+    Ok(list.servers)
+}
@@ -7,6 +7,9 @@ use uuid::Uuid;
 
 use crate::Error;
 use crate::Result;
+use crate::mirror;
+use crate::signing::{self, ManifestVerifier, SignedManifest};
+use crate::state::RefreshToken;
 use crate::util;
 
 #[allow(dead_code)]
@@ -44,10 +47,36 @@ pub struct AuthRequest {
     password: String,
 }
 
+/// Some servers return the refresh token wrapped in JSON along with its expiry
+#[derive(Deserialize)]
+struct AuthResponse {
+    token: String,
+    expires: Option<u64>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct Session {
     username: String,
     session_token: String,
+    // servers that rotate refresh tokens hand out a new one with each session
+    #[serde(default, skip_serializing)]
+    refresh_token: Option<String>,
+    #[serde(default, skip_serializing)]
+    refresh_token_expires: Option<u64>,
+}
+impl Session {
+    pub fn get_username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn get_session_token(&self) -> &str {
+        &self.session_token
+    }
+
+    pub fn take_refresh_token(&mut self) -> Option<RefreshToken> {
+        let token = self.refresh_token.take()?;
+        Some(RefreshToken::new(&token, self.refresh_token_expires.take()))
+    }
 }
 
 #[derive(Serialize)]
@@ -105,7 +134,11 @@ pub struct PasswordUpdateRequest {
 }
 
 fn make_api_error(url: &str, status: StatusCode, body: &str) -> Error {
-    format!("API error {}: {} [{}]", status, body, url).into()
+    Error::ApiError {
+        status: status.as_u16(),
+        url: url.to_string(),
+        body: body.to_string(),
+    }
 }
 
 pub async fn get_info(endpoint_host: &str) -> Result<InfoResponse> {
@@ -172,7 +205,7 @@ pub async fn get_refresh_token(
     username: &str,
     password: &str,
     endpoint_host: &str,
-) -> Result<String> {
+) -> Result<RefreshToken> {
     debug!("Getting token for {}", username);
     let req = AuthRequest {
         username: username.to_string(),
@@ -185,9 +218,13 @@ pub async fn get_refresh_token(
     let status = res.status();
     let body = res.text().await?;
     if status.is_success() {
-        Ok(body)
+        let token = match serde_json::from_str::<AuthResponse>(&body) {
+            Ok(auth) => RefreshToken::new(&auth.token, auth.expires),
+            Err(_) => RefreshToken::new(&body, None),
+        };
+        Ok(token)
     } else if status == StatusCode::UNAUTHORIZED {
-        Err("Incorrect username or password".into())
+        Err(Error::InvalidCredentials)
     } else {
         Err(make_api_error(&url, status, &body))
     }
@@ -208,6 +245,9 @@ pub async fn get_session(refresh_token: &str, endpoint_host: &str) -> Result<Ses
     if status.is_success() {
         let session: Session = serde_json::from_str(&body)?;
         Ok(session)
+    } else if status == StatusCode::UNAUTHORIZED {
+        // refresh token was rejected; it's expired or was revoked
+        Err(Error::SessionExpired)
     } else {
         Err(make_api_error(&url, status, &body))
     }
@@ -258,41 +298,61 @@ pub async fn get_cookie(token: &str, endpoint_host: &str) -> Result<(String, Str
     Ok((cookie.username, cookie.cookie))
 }
 
-// need to use std result here for future safety
-async fn fetch_version_internal(
-    endpoint_host: &str,
-    filename: &str,
-) -> std::result::Result<Version, String> {
+/// Returns the manifest's URL along with its contents
+async fn fetch_version_json(endpoint_host: &str, filename: &str) -> Result<(String, String)> {
     let version_endpoint = format!("https://{}/versions/{}", endpoint_host, filename);
-    let version_json = util::do_simple_get(&version_endpoint)
-        .await
-        .map_err(|e| e.to_string())?;
-    let version: Version = serde_json::from_str(&version_json).map_err(|e| e.to_string())?;
-    Ok(version)
+    let version_json = util::do_simple_get(&version_endpoint).await?;
+    Ok((version_endpoint, version_json))
 }
 
-pub async fn fetch_version(endpoint_host: &str, version_uuid: Uuid) -> Result<Version> {
+/// A version manifest fetched from an endpoint
+pub struct FetchedVersion {
+    pub version: Version,
+    /// `Version` doesn't keep the manifest's mirrors
+    pub mirrors: Vec<String>,
+    /// The manifest as published, for passing on to other launchers
+    pub original: SignedManifest,
+    /// Set if the manifest was accepted despite not being trusted
+    pub warning: Option<String>,
+}
+
+/// Fetches a version manifest from the endpoint and checks its signature.
+pub async fn fetch_version(
+    endpoint_host: &str,
+    version_uuid: Uuid,
+    verifier: &ManifestVerifier,
+) -> Result<FetchedVersion> {
     debug!("Fetching version {}", version_uuid);
-    let mut version = fetch_version_internal(endpoint_host, &version_uuid.to_string()).await;
-    if version.is_err() {
+    let fetched = match fetch_version_json(endpoint_host, &version_uuid.to_string()).await {
+        Ok(fetched) => Ok(fetched),
         // try with .json extension
-        version = fetch_version_internal(endpoint_host, &format!("{}.json", version_uuid)).await;
-    }
-    match version {
-        Ok(v) => {
-            if v.get_uuid() != version_uuid {
-                return Err(
-                    format!("Version mismatch: {} != {}", v.get_uuid(), version_uuid).into(),
-                );
-            }
-
-            Ok(v)
-        }
-        Err(e) => {
-            error!("Failed to fetch version {}: {}", version_uuid, e);
-            Err(e.to_string().into())
-        }
+        Err(_) => fetch_version_json(endpoint_host, &format!("{}.json", version_uuid)).await,
+    };
+    let (version_url, version_json) = fetched.inspect_err(|e| {
+        error!("Failed to fetch version {}: {}", version_uuid, e);
+    })?;
+
+    // fetched even if we don't check it, so it can go along with the manifest
+    let signature = signing::fetch_signature(&version_url).await;
+    let original = SignedManifest::new(version_json, signature);
+    let warning = original.check(verifier, &format!("Manifest for version {}", version_uuid))?;
+
+    let version = original.parse()?;
+    if version.get_uuid() != version_uuid {
+        return Err(format!(
+            "Version mismatch: {} != {}",
+            version.get_uuid(),
+            version_uuid
+        )
+        .into());
     }
+    let mirrors = mirror::parse_manifest_mirrors(&original.manifest);
+    Ok(FetchedVersion {
+        version,
+        mirrors,
+        original,
+        warning,
+    })
 }
 
 pub async fn send_otp(endpoint_host: &str, email: &str) -> Result<()> {
@@ -0,0 +1,189 @@
+use std::fmt;
+
+use serde::{Serialize, ser::SerializeStruct as _};
+use uuid::Uuid;
+
+/// Errors surfaced by the launcher backend.
+/// Serialized to the frontend as `{ kind, message, details }` so the UI
+/// can react to specific failures without parsing the message text.
+#[derive(Debug)]
+pub enum LauncherError {
+    NotLoggedIn,
+    SessionExpired,
+    InvalidCredentials,
+    ServerNotFound(Uuid),
+    FolderNotFound(Uuid),
+    VersionNotFound(Uuid),
+    VersionNotSupported(Uuid),
+    NotEndpointServer,
+    LoginServerUnreachable {
+        addr: String,
+        reason: String,
+    },
+    ApiError {
+        status: u16,
+        url: String,
+        body: String,
+    },
+    CacheBusy,
+    Cancelled,
+    InvalidSignature(String),
+    UnsignedManifest(String),
+    TokenStoreLocked,
+    InvalidPassphrase,
+    Io(std::io::Error),
+    Http(reqwest::Error),
+    Json(serde_json::Error),
+    Other(String),
+}
+impl LauncherError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotLoggedIn => "not_logged_in",
+            Self::SessionExpired => "session_expired",
+            Self::InvalidCredentials => "invalid_credentials",
+            Self::ServerNotFound(_) => "server_not_found",
+            Self::FolderNotFound(_) => "folder_not_found",
+            Self::VersionNotFound(_) => "version_not_found",
+            Self::VersionNotSupported(_) => "version_not_supported",
+            Self::NotEndpointServer => "not_endpoint_server",
+            Self::LoginServerUnreachable { .. } => "login_server_unreachable",
+            Self::ApiError { .. } => "api_error",
+            Self::CacheBusy => "cache_busy",
+            Self::Cancelled => "cancelled",
+            Self::InvalidSignature(_) => "invalid_signature",
+            Self::UnsignedManifest(_) => "unsigned_manifest",
+            Self::TokenStoreLocked => "token_store_locked",
+            Self::InvalidPassphrase => "invalid_passphrase",
+            Self::Io(_) => "io",
+            Self::Http(_) => "http",
+            Self::Json(_) => "json",
+            Self::Other(_) => "other",
+        }
+    }
+
+    fn details(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::ServerNotFound(uuid)
+            | Self::FolderNotFound(uuid)
+            | Self::VersionNotFound(uuid)
+            | Self::VersionNotSupported(uuid) => Some(serde_json::json!({ "uuid": uuid })),
+            Self::LoginServerUnreachable { addr, .. } => Some(serde_json::json!({ "addr": addr })),
+            Self::ApiError { status, url, body } => Some(serde_json::json!({
+                "status": status,
+                "url": url,
+                "body": body,
+            })),
+            Self::Http(e) => e
+                .url()
+                .map(|url| serde_json::json!({ "url": url.as_str() })),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for LauncherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotLoggedIn => write!(f, "Not logged in"),
+            Self::SessionExpired => write!(f, "Session expired, please log in again"),
+            Self::InvalidCredentials => write!(f, "Incorrect username or password"),
+            Self::ServerNotFound(uuid) => write!(f, "Server {} not found", uuid),
+            Self::FolderNotFound(uuid) => write!(f, "Folder {} not found", uuid),
+            Self::VersionNotFound(uuid) => write!(f, "Version {} not found", uuid),
+            Self::VersionNotSupported(uuid) => {
+                write!(f, "Version {} not supported by server", uuid)
+            }
+            Self::NotEndpointServer => write!(f, "Server is not an endpoint server"),
+            Self::LoginServerUnreachable { addr, reason } => {
+                write!(f, "Login server unreachable ({}): {}", addr, reason)
+            }
+            Self::ApiError { status, url, body } => {
+                write!(f, "API error {}: {} [{}]", status, body, url)
+            }
+            Self::CacheBusy => write!(f, "Cache operation in progress"),
+            Self::Cancelled => write!(f, "Cancelled"),
+            Self::InvalidSignature(what) => {
+                write!(f, "{} is not signed by a trusted key", what)
+            }
+            Self::UnsignedManifest(what) => write!(f, "{} is not signed", what),
+            Self::TokenStoreLocked => {
+                write!(f, "Saved logins are locked, enter your passphrase first")
+            }
+            Self::InvalidPassphrase => write!(f, "Incorrect passphrase"),
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Http(e) => write!(f, "{}", e),
+            Self::Json(e) => write!(f, "{}", e),
+            Self::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for LauncherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Http(e) => Some(e),
+            Self::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl Serialize for LauncherError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut s = serializer.serialize_struct("LauncherError", 3)?;
+        s.serialize_field("kind", self.kind())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("details", &self.details())?;
+        s.end()
+    }
+}
+
+impl From<std::io::Error> for LauncherError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<reqwest::Error> for LauncherError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Http(e)
+    }
+}
+
+impl From<serde_json::Error> for LauncherError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl From<String> for LauncherError {
+    fn from(msg: String) -> Self {
+        Self::Other(msg)
+    }
+}
+
+impl From<&str> for LauncherError {
+    fn from(msg: &str) -> Self {
+        Self::Other(msg.to_string())
+    }
+}
+
+impl From<uuid::Error> for LauncherError {
+    fn from(e: uuid::Error) -> Self {
+        Self::Other(format!("Invalid UUID: {}", e))
+    }
+}
+
+impl From<std::num::ParseIntError> for LauncherError {
+    fn from(e: std::num::ParseIntError) -> Self {
+        Self::Other(e.to_string())
+    }
+}
+
+impl From<tokio::sync::AcquireError> for LauncherError {
+    fn from(e: tokio::sync::AcquireError) -> Self {
+        Self::Other(e.to_string())
+    }
+}
@@ -0,0 +1,462 @@
+use std::{
+    collections::HashSet,
+    process::{Child, Command, Stdio},
+    sync::atomic::AtomicBool,
+};
+
+use log::*;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    Error, LOGIN_COOKIE_REGEX, Result,
+    cache::{self, EvictionPlanner},
+    config, endpoint, mirror,
+    proxy::{self, ProxyCache},
+    state::{AppState, ServerInfo, get_app_statics},
+    util::{self, AlertSink, AlertVariant},
+};
+
+/// Whether a game started from the launcher window is still running
+pub(crate) static GAME_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Finds the first host with the version's main file, trying the version's own URL
+/// before its mirrors and HTTPS before HTTP, since ffrunner supports it.
+/// Returns that host's asset base URL and main file URL.
+async fn find_main_file(
+    asset_url: &str,
+    main_url: &str,
+    mirrors: &[String],
+) -> Result<(String, String)> {
+    let bases: Vec<String> = std::iter::once(asset_url.to_string())
+        .chain(mirrors.iter().cloned())
+        .collect();
+    for base in mirror::order_by_health(&bases) {
+        let url = if base == asset_url {
+            main_url.to_string()
+        } else {
+            format!("{}/{}", base, util::MAIN_FILE_NAME)
+        };
+        let mut candidates = Vec::with_capacity(2);
+        if url.starts_with("http://") {
+            candidates.push(url.replacen("http://", "https://", 1));
+        }
+        candidates.push(url);
+        // a host that answers at all is fine, even if it doesn't have the file
+        let mut answered = false;
+        for candidate in candidates {
+            match util::get_web_file_status(&candidate).await {
+                Ok(status) if status.is_success() => {
+                    mirror::mark_ok(&base);
+                    return Ok((base, candidate));
+                }
+                Ok(status) if !mirror::is_host_failure(status) => answered = true,
+                Ok(status) => debug!("{} answered {}", candidate, status),
+                Err(e) => debug!("Failed to check {}: {}", candidate, e),
+            }
+        }
+        if !answered {
+            mirror::mark_failed(&base);
+        }
+    }
+    Err(format!("Main file not found: {}", main_url).into())
+}
+
+/// Builds the ffrunner command for the given server and version and stores it
+/// in `state.launch_cmd`, starting the asset proxy if needed.
+/// Returns an optional delay in seconds to give the user time to read any warnings.
+pub(crate) async fn prep_launch(
+    app_state: &Mutex<AppState>,
+    alerts: &AlertSink,
+    server_uuid: Uuid,
+    version_uuid: Uuid,
+    session_token: Option<String>,
+) -> Result<Option<usize>> {
+    // timeout in seconds, for giving the user time
+    // to see any warnings before launch
+    let mut timeout_sec = None;
+
+    let app_statics = get_app_statics();
+    let working_dir = &app_statics.resource_dir;
+    let mut ffrunner_path = working_dir.clone();
+    ffrunner_path.push("ffrunner.exe");
+    let mut cmd = Command::new(ffrunner_path.clone());
+    cmd.current_dir(working_dir);
+
+    // contacting the server can take a while, so don't hold the state meanwhile
+    let (server, address_family) = {
+        let state = app_state.lock().await;
+        let server = state
+            .servers
+            .get_entry(server_uuid)
+            .ok_or(Error::ServerNotFound(server_uuid))?
+            .clone();
+        (server, state.config.launcher.address_family)
+    };
+
+    let mut server_name = server.get_description();
+    let addr;
+    let mut versions = Vec::new();
+    let mut custom_loading_screen = false;
+    let mut custom_icon_url = None;
+    match &server.info {
+        ServerInfo::Simple { ip, version } => {
+            addr = ip.clone();
+            versions.push(version.clone());
+        }
+        ServerInfo::Endpoint { endpoint, .. } => {
+            // Ask the endpoint server for the UUID of the current version
+            let Ok(api_info) = endpoint::get_info(endpoint).await else {
+                return Err("Failed to contact API server".into());
+            };
+
+            if api_info.custom_loading_screen.is_some_and(|b| b) {
+                custom_loading_screen = true;
+            }
+
+            if let Ok(icon_url) = endpoint::get_custom_icon_url(endpoint).await {
+                custom_icon_url = icon_url;
+            }
+
+            server_name = Some(format!("\"{}\"", api_info.server_name));
+            addr = api_info.login_address.clone();
+            versions = api_info.get_supported_versions();
+        }
+    }
+
+    // Ensure the version is supported
+    if !versions.contains(&version_uuid.to_string()) {
+        return Err(Error::VersionNotSupported(version_uuid));
+    }
+
+    let ip = util::resolve_server_addr(&addr, address_family).await?;
+
+    // Make sure the game can actually get in before we hand off to ffrunner,
+    // since the client just hangs on a dead login server
+    let latency = util::probe_game_addr(&ip).await?;
+    info!("Login server {} reachable ({} ms)", ip, latency.as_millis());
+
+    let mut state = app_state.lock().await;
+
+    let Some(version) = state.versions.get_entry(version_uuid).cloned() else {
+        return Err(Error::VersionNotFound(version_uuid));
+    };
+    let version = &version;
+
+    let base_cache_dir = &state.config.launcher.game_cache_path;
+    let cache_dir = util::get_cache_dir_for_version(base_cache_dir, version);
+    if !cache_dir.exists() {
+        // check for cache upgrade
+        if let Some(parent_uuid) = version.get_parent_uuid() {
+            if let Some(parent_version) = state.versions.get_entry(parent_uuid) {
+                let parent_cache_dir =
+                    util::get_cache_dir_for_version(base_cache_dir, parent_version);
+                if parent_cache_dir.exists() {
+                    if let Err(e) = util::copy_dir(&parent_cache_dir, &cache_dir) {
+                        warn!(
+                            "Failed to upgrade cache from parent version {} for {}: {}",
+                            parent_uuid, version_uuid, e
+                        );
+                    } else {
+                        info!(
+                            "Upgraded cache from parent version {} for {}",
+                            parent_uuid, version_uuid
+                        );
+
+                        if state.config.launcher.delete_old_game_caches
+                            && state.get_version_use_count(parent_uuid) == 0
+                        {
+                            if let Err(e) = util::delete_dir(&parent_cache_dir) {
+                                warn!(
+                                    "Failed to delete cache for parent version {}: {}",
+                                    parent_uuid, e
+                                );
+                            } else {
+                                info!("Deleted cache for parent version {}", parent_uuid);
+                                cache::discard_validation_report(&mut state, parent_uuid, false);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let _ = std::fs::create_dir_all(&cache_dir);
+    cmd.env("UNITY_FF_CACHE_DIR", cache_dir);
+
+    state.cache_usage.touch(version_uuid);
+    if let Err(e) = state.cache_usage.save() {
+        warn!("Failed to save cache usage: {}", e);
+    }
+
+    if state.config.launcher.game_cache_quota.is_some() {
+        // keep anything the server might ask for
+        let protected: HashSet<Uuid> = versions
+            .iter()
+            .filter_map(|v| Uuid::parse_str(v).ok())
+            .chain([version_uuid])
+            .collect();
+        let planner = EvictionPlanner::new(&state);
+        // measuring and deleting caches takes a while, so let everything else carry on meanwhile
+        drop(state);
+        let plan = tauri::async_runtime::spawn_blocking(move || planner.plan(&protected))
+            .await
+            .map_err(|e| e.to_string())?;
+        if !plan.is_empty() {
+            let freed = plan.execute(app_state).await;
+            info!("Evicted {} bytes of game caches to stay under quota", freed);
+        }
+        state = app_state.lock().await;
+    }
+
+    let mut asset_url = version.get_asset_url();
+    let mut main_url =
+        version
+            .get_main_file_url()
+            .unwrap_or(format!("{}/{}", asset_url, util::MAIN_FILE_NAME));
+
+    // use offline cache if available
+    let base_offline_cache_dir = &state.config.launcher.offline_cache_path;
+    let offline_cache_dir = util::get_cache_dir_for_version(base_offline_cache_dir, version);
+    // partial offline caches get filled in by the asset proxy instead
+    let mut offline_cache_corrupt = false;
+    if state.config.launcher.use_offline_caches
+        && util::is_offline_cache_complete(version, &offline_cache_dir)
+    {
+        let offline_asset_url = util::get_path_as_file_uri(&offline_cache_dir);
+        let offline_cache_dir = offline_cache_dir.to_string_lossy().to_string();
+        let offline_main_url = format!("{}\\{}", offline_cache_dir, util::MAIN_FILE_NAME);
+
+        let use_offline_cache = if state.config.launcher.verify_offline_caches {
+            match version
+                .validate_compressed_stop_on_first_fail(&offline_cache_dir, None)
+                .await
+            {
+                Ok(corrupted) => {
+                    let is_corrupt = corrupted.is_some();
+                    offline_cache_corrupt = is_corrupt;
+                    if is_corrupt {
+                        let msg = format!(
+                            "Offline cache for version {} is corrupt. Not using.",
+                            util::get_version_name(version)
+                        );
+                        warn!("{}", msg);
+                        alerts.send(AlertVariant::Warning, &msg);
+                        timeout_sec = Some(3);
+                    }
+                    !is_corrupt
+                }
+                Err(e) => {
+                    let msg = format!(
+                        "Failed to validate offline cache for version {}: {:?}",
+                        util::get_version_name(version),
+                        e
+                    );
+                    error!("{}", msg);
+                    alerts.send(AlertVariant::Error, &msg);
+                    false
+                }
+            }
+        } else {
+            true
+        };
+
+        if use_offline_cache {
+            let msg = format!(
+                "Using offline cache for version {}",
+                util::get_version_name(version)
+            );
+            info!("{}", msg);
+            alerts.send(AlertVariant::Success, &msg);
+
+            asset_url = offline_asset_url;
+            main_url = offline_main_url;
+        }
+    }
+
+    let mirrors = state.versions.get_mirrors(version_uuid).to_vec();
+    let use_proxy = state.config.launcher.proxy_asset_downloads
+        || state.config.launcher.download_speed_limit.is_some();
+
+    // the offline cache's main file is a local path, so there's nothing to check
+    if main_url.starts_with("http") {
+        let (base_url, found_main_url) = find_main_file(&asset_url, &main_url, &mirrors).await?;
+        main_url = found_main_url;
+        if !use_proxy {
+            asset_url = base_url;
+        }
+    }
+
+    if use_proxy && asset_url.starts_with("http") {
+        // with offline caches on, whatever the game downloads gets saved to the offline cache,
+        // so the version becomes playable offline over time
+        let cache = state
+            .config
+            .launcher
+            .use_offline_caches
+            .then(|| ProxyCache::new(version, offline_cache_dir.clone()))
+            .filter(|_| !offline_cache_corrupt);
+        let proxy_main_url = cache.is_some().then_some(main_url.as_str());
+        let (proxy_url, handle) =
+            proxy::start_asset_proxy(&asset_url, &mirrors, proxy_main_url, cache).await?;
+        if proxy_main_url.is_some() {
+            main_url = format!("{}/{}", proxy_url, util::MAIN_FILE_NAME);
+        }
+        asset_url = proxy_url;
+        state.proxy = Some(handle);
+    }
+
+    debug!("Asset URL: {}", asset_url);
+    debug!("Main URL: {}", main_url);
+
+    let log_file_path = format!(
+        "\"{}\"",
+        app_statics
+            .ffrunner_log_path
+            .clone()
+            .to_str()
+            .ok_or("Invalid log file path")?
+    );
+
+    cmd.args(["-m", &main_url])
+        .args(["-a", &ip])
+        .args(["--asseturl", &format!("{}/", asset_url)])
+        .args(["-l", &log_file_path]);
+
+    if let Some(server_name) = server_name {
+        // window title
+        cmd.args(["-n", &server_name]);
+    }
+
+    if let Some(icon_url) = custom_icon_url {
+        // window icon
+        cmd.args(["-i", &icon_url]);
+    }
+
+    if let ServerInfo::Endpoint { endpoint, .. } = &server.info {
+        match session_token {
+            None => {
+                warn!("No session token provided for endpoint server");
+            }
+            Some(token) => {
+                let (username, cookie) = endpoint::get_cookie(&token, endpoint).await?;
+                cmd.args(["-u", &username]).args(["-t", &cookie]);
+            }
+        };
+
+        cmd.args(["-e", endpoint]);
+
+        if custom_loading_screen {
+            cmd.arg("--loader-images");
+        }
+    }
+
+    // Window size
+    if let Some(window_size) = &state.config.game.window_size {
+        cmd.args([
+            "--width",
+            &window_size.width.to_string(),
+            "--height",
+            &window_size.height.to_string(),
+        ]);
+    }
+
+    // Graphics API overrides
+    match state.config.game.graphics_api {
+        config::GraphicsApi::Dx9 => {}
+        config::GraphicsApi::OpenGl => {
+            cmd.arg("--force-opengl");
+        }
+        config::GraphicsApi::Vulkan => {
+            cmd.arg("--force-vulkan");
+            #[cfg(debug_assertions)]
+            cmd.env("DXVK_HUD", "1");
+        }
+    }
+
+    // FPS behavior
+    match state.config.game.fps_fix {
+        config::FpsFix::On => {}
+        config::FpsFix::OnWithLimiter(limit) => {
+            cmd.env("UNITY_FF_FPS_CAP", limit.to_string());
+        }
+        config::FpsFix::Off => {
+            cmd.env("UNITY_FF_FPS_CAP", "old");
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    cmd.arg("-v"); // verbose logging
+
+    if !state.launch_profiles.has_entries() {
+        return Err("No launch profiles found in game settings. Please create one.".into());
+    }
+
+    let selected_launch_profile = state.config.game.launch_profile;
+    let profile = state
+        .launch_profiles
+        .get(selected_launch_profile)
+        .ok_or(format!(
+            "Launch profile '{}' not found",
+            selected_launch_profile
+        ))?;
+
+    let launch_fmt = profile.get_command();
+    cmd = util::gen_launch_command(cmd, launch_fmt);
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        // Prefix setup
+        let mut compat_data_dir = util::get_compat_data_dir(&cmd);
+        if compat_data_dir.is_none() {
+            // not specified; use launcher compat data dir
+            let mut launcher_compat_dir = app_statics.compat_data_dir.clone();
+            launcher_compat_dir.push(profile.get_id().to_string());
+
+            if cmd.get_program().to_string_lossy().ends_with("proton") {
+                cmd.env(
+                    "STEAM_COMPAT_DATA_PATH",
+                    launcher_compat_dir.to_string_lossy().to_string(),
+                );
+                // proton sets WINEPREFIX internally
+            } else {
+                // assume wine
+                cmd.env(
+                    "WINEPREFIX",
+                    launcher_compat_dir.to_string_lossy().to_string(),
+                );
+            }
+            compat_data_dir = Some(launcher_compat_dir);
+        }
+
+        let compat_data_dir = compat_data_dir.unwrap();
+        if !compat_data_dir.exists() {
+            debug!("Creating prefix at {}", compat_data_dir.to_string_lossy());
+            std::fs::create_dir_all(&compat_data_dir)?;
+        }
+    }
+
+    // Detach stdio so the child doesn't crash from broken pipes
+    // when the launcher exits (e.g. LaunchBehavior::Quit)
+    cmd.stdin(Stdio::null());
+    cmd.stdout(Stdio::null());
+    cmd.stderr(Stdio::null());
+
+    util::log_command(&cmd);
+    state.launch_cmd = Some(cmd);
+    Ok(timeout_sec)
+}
+
+/// Spawns a prepared launch command, censoring the login cookie in any error
+pub(crate) fn spawn_game(cmd: &mut Command) -> Result<Child> {
+    let cmd_str = util::get_launch_cmd_dbg_str(cmd, false);
+    let proc = cmd.spawn().map_err(|e| {
+        // we want to censor the login cookie if present
+        let censored_cmd_str = LOGIN_COOKIE_REGEX
+            .replace_all(&cmd_str, "-t ***")
+            .to_string();
+        format!("{} (launch command was: {})", e, censored_cmd_str)
+    })?;
+    Ok(proc)
+}
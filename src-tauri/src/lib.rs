@@ -1,31 +1,55 @@
+mod archive;
+mod bundle;
+mod cache;
+mod cli;
 mod config;
+mod deep_link;
+mod directory;
 mod endpoint;
+mod error;
+mod launch;
+mod mirror;
+mod proxy;
+mod signing;
 mod state;
+mod status;
+mod token_store;
 mod util;
+mod verify;
 
+use cache::{
+    CacheCollectResult, CacheDirTarget, CacheInventory, CacheScanReport, EvictionPlan,
+    EvictionPlanner, ValidationReport,
+};
 use config::{LaunchBehavior, LauncherSettings};
 use endpoint::{AccountInfo, InfoResponse, RegisterResponse, Session};
 use ffbuildtool::{ItemProgress, Version};
 use regex::Regex;
-use rust_proxy::proxy::tcp::TcpProxy;
 use serde::{Deserialize, Serialize};
+use signing::{SignedManifest, TrustedKey};
 use state::{
-    AppState, Config, FlatServer, FlatServers, Server, ServerInfo, Versions, get_app_statics,
+    AppState, Config, FlatServer, FlatServers, SavedAccountsView, Server, ServerBundle, ServerInfo,
+    VersionOrigin, Versions, get_app_statics,
 };
+use status::ServerStatus;
+use tauri_plugin_deep_link::DeepLinkExt;
 use tauri_plugin_shell::ShellExt;
-use util::AlertVariant;
+use token_store::TokenStoreStatus;
+use util::{AlertSink, AlertVariant};
 
 use std::{
     collections::{HashMap, HashSet},
     env,
-    process::Stdio,
-    sync::{Arc, LazyLock, OnceLock, mpsc},
+    path::{Path, PathBuf},
+    sync::{
+        Arc, LazyLock, OnceLock,
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
     vec,
 };
-use tokio::{
-    net::TcpListener,
-    sync::{Mutex, Semaphore},
-};
+use tokio::sync::{Mutex, Semaphore};
+use tokio_util::sync::CancellationToken;
 
 use log::*;
 use tauri::Manager;
@@ -33,9 +57,9 @@ use uuid::Uuid;
 
 use crate::state::{LaunchProfile, LaunchProfilesView};
 
-type Error = Box<dyn std::error::Error>;
+type Error = error::LauncherError;
 type Result<T> = std::result::Result<T, Error>;
-type CommandResult<T> = std::result::Result<T, String>;
+type CommandResult<T> = std::result::Result<T, Error>;
 
 static LOGIN_COOKIE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"-t (\S+)"#).unwrap());
 
@@ -56,18 +80,57 @@ struct UpdateInfo {
     url: String,
 }
 
-const MAX_CONCURRENT_VALIDATION_OPS: usize = 2; // compromise. 1 is a lot for an HDD, but SSDs can handle 3-5
-static VALIDATION_SEMAPHORE: Semaphore = Semaphore::const_new(MAX_CONCURRENT_VALIDATION_OPS);
-
-const MAX_CONCURRENT_DOWNLOAD_OPS: usize = 1;
-static DOWNLOAD_SEMAPHORE: Semaphore = Semaphore::const_new(MAX_CONCURRENT_DOWNLOAD_OPS);
+// sized from the launcher settings by `apply_limits`
+static VALIDATION_SEMAPHORE: Semaphore =
+    Semaphore::const_new(config::DEFAULT_MAX_CONCURRENT_VALIDATIONS);
+static VALIDATION_PERMITS: AtomicUsize =
+    AtomicUsize::new(config::DEFAULT_MAX_CONCURRENT_VALIDATIONS);
+
+static DOWNLOAD_SEMAPHORE: Semaphore =
+    Semaphore::const_new(config::DEFAULT_MAX_CONCURRENT_DOWNLOADS);
+static DOWNLOAD_PERMITS: AtomicUsize = AtomicUsize::new(config::DEFAULT_MAX_CONCURRENT_DOWNLOADS);
+
+// running cache ops by version, each with the token that cancels it
+static GAME_CACHE_OPS: OnceLock<Mutex<HashMap<Uuid, CancellationToken>>> = OnceLock::new();
+static OFFLINE_CACHE_OPS: OnceLock<Mutex<HashMap<Uuid, CancellationToken>>> = OnceLock::new();
+
+/// Grows or shrinks a semaphore to `target` permits. Shrinking has to wait
+/// for enough running operations to finish, so it happens in the background.
+fn resize_semaphore(semaphore: &'static Semaphore, permits: &AtomicUsize, target: usize) {
+    let target = target.max(1);
+    let current = permits.swap(target, Ordering::Relaxed);
+    if target > current {
+        semaphore.add_permits(target - current);
+    } else if target < current {
+        let excess = (current - target) as u32;
+        tauri::async_runtime::spawn(async move {
+            if let Ok(permits) = semaphore.acquire_many(excess).await {
+                permits.forget();
+            }
+        });
+    }
+}
 
-static GAME_CACHE_OPS: OnceLock<Mutex<HashSet<Uuid>>> = OnceLock::new();
-static OFFLINE_CACHE_OPS: OnceLock<Mutex<HashSet<Uuid>>> = OnceLock::new();
+/// Applies the concurrency and bandwidth limits from the launcher settings
+fn apply_limits(settings: &LauncherSettings) {
+    resize_semaphore(
+        &DOWNLOAD_SEMAPHORE,
+        &DOWNLOAD_PERMITS,
+        settings.max_concurrent_downloads,
+    );
+    resize_semaphore(
+        &VALIDATION_SEMAPHORE,
+        &VALIDATION_PERMITS,
+        settings.max_concurrent_validations,
+    );
+    proxy::DOWNLOAD_THROTTLE.set_rate(settings.download_speed_limit);
+}
 
 const CACHE_PROGRESS_EVENT: &str = "cache_progress";
+const SERVERS_CHANGED_EVENT: &str = "servers_changed";
+const ADD_SERVER_REQUESTED_EVENT: &str = "add_server_requested";
 
-#[derive(Debug, Serialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct CacheProgressItem {
     item_size: u64,
     corrupt: bool,
@@ -78,6 +141,7 @@ struct CacheProgressItem {
 enum CacheEvent {
     ItemProcessed(String, CacheProgressItem),
     Done,
+    Cancelled,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -86,6 +150,7 @@ struct CacheProgress {
     offline: bool,
     items: HashMap<String, CacheProgressItem>,
     done: bool,
+    cancelled: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -94,7 +159,7 @@ struct ImportCounts {
     server_count: usize,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 struct NewServerDetails {
     description: String,
     ip: Option<String>,
@@ -110,16 +175,9 @@ async fn do_launch(app_handle: tauri::AppHandle) -> CommandResult<i32> {
     let proxy_enabled = state.config.launcher.proxy_asset_downloads;
     let launch_behavior = state.config.launcher.launch_behavior;
     let mut cmd = state.launch_cmd.take().ok_or("No launch prepared")?;
-    let cmd_str = util::get_launch_cmd_dbg_str(&cmd, false);
     drop(state);
 
-    let mut proc = cmd.spawn().map_err(|e| {
-        // we want to censor the login cookie if present
-        let censored_cmd_str = LOGIN_COOKIE_REGEX
-            .replace_all(&cmd_str, "-t ***")
-            .to_string();
-        format!("{} (launch command was: {})", e, censored_cmd_str)
-    })?;
+    let mut proc = launch::spawn_game(&mut cmd)?;
 
     if launch_behavior == LaunchBehavior::Quit && !proxy_enabled {
         // no need to keep the proxy alive; we can quit immediately
@@ -127,7 +185,9 @@ async fn do_launch(app_handle: tauri::AppHandle) -> CommandResult<i32> {
         return Ok(0);
     }
 
+    launch::GAME_RUNNING.store(true, Ordering::SeqCst);
     let exit_result = proc.wait();
+    launch::GAME_RUNNING.store(false, Ordering::SeqCst);
 
     // shutdown the asset proxy
     let state = app_handle.state::<Mutex<AppState>>();
@@ -142,7 +202,7 @@ async fn do_launch(app_handle: tauri::AppHandle) -> CommandResult<i32> {
         return Ok(0);
     }
 
-    let exit_code = exit_result.map_err(|e| e.to_string())?;
+    let exit_code = exit_result?;
     Ok(exit_code.code().unwrap_or(0))
 }
 
@@ -160,10 +220,10 @@ async fn do_register(
         let server = state
             .servers
             .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?;
+            .ok_or(Error::ServerNotFound(server_uuid))?;
 
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
@@ -171,7 +231,7 @@ async fn do_register(
         Ok(response)
     };
     debug!("do_register");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -188,12 +248,15 @@ async fn do_login(
         let mut server = state
             .servers
             .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?
+            .ok_or(Error::ServerNotFound(server_uuid))?
             .clone();
 
         let ServerInfo::Endpoint { endpoint, .. } = server.info else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
+        if remember && state.tokens.is_locked() {
+            return Err(Error::TokenStoreLocked);
+        }
         drop(state);
 
         let refresh_token = endpoint::get_refresh_token(&username, &password, &endpoint).await?;
@@ -210,7 +273,11 @@ async fn do_login(
         }
 
         if remember {
-            state.tokens.save_token(server_uuid, &refresh_token);
+            state
+                .tokens
+                .save_token(server_uuid, &username, refresh_token);
+            // the account just logged into becomes the default for this server
+            state.tokens.select_account(server_uuid, &username)?;
             state.save();
         } else {
             state.temp_tokens.insert(server_uuid, refresh_token);
@@ -218,17 +285,21 @@ async fn do_login(
         Ok(())
     };
     debug!("do_login");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
-async fn do_logout(app_handle: tauri::AppHandle, server_uuid: Option<Uuid>) -> CommandResult<()> {
+async fn do_logout(
+    app_handle: tauri::AppHandle,
+    server_uuid: Option<Uuid>,
+    username: Option<String>,
+) -> CommandResult<()> {
     let internal = async {
         let state = app_handle.state::<Mutex<AppState>>();
         let mut state = state.lock().await;
         match server_uuid {
             Some(uuid) => {
-                state.tokens.remove_token(uuid);
+                state.tokens.remove_token(uuid, username.as_deref());
             }
             None => {
                 state.tokens.clear();
@@ -238,7 +309,51 @@ async fn do_logout(app_handle: tauri::AppHandle, server_uuid: Option<Uuid>) -> C
         Ok(())
     };
     debug!("do_logout");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+#[tauri::command]
+async fn get_saved_accounts(app_handle: tauri::AppHandle, server_uuid: Uuid) -> SavedAccountsView {
+    debug!("get_saved_accounts {}", server_uuid);
+    let state = app_handle.state::<Mutex<AppState>>();
+    let state = state.lock().await;
+    state.tokens.get_accounts(server_uuid)
+}
+
+#[tauri::command]
+async fn select_account(
+    app_handle: tauri::AppHandle,
+    server_uuid: Uuid,
+    username: String,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.tokens.select_account(server_uuid, &username)?;
+        state.save();
+        Ok(())
+    };
+    debug!("select_account {}", server_uuid);
+    internal.await
+}
+
+#[tauri::command]
+async fn get_token_store_status(app_handle: tauri::AppHandle) -> TokenStoreStatus {
+    debug!("get_token_store_status");
+    let state = app_handle.state::<Mutex<AppState>>();
+    let state = state.lock().await;
+    state.tokens.get_store_status()
+}
+
+#[tauri::command]
+async fn unlock_token_store(app_handle: tauri::AppHandle, passphrase: String) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.tokens.unlock(&passphrase)
+    };
+    debug!("unlock_token_store");
+    internal.await
 }
 
 #[tauri::command]
@@ -253,10 +368,10 @@ async fn send_otp(
         let server = state
             .servers
             .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?;
+            .ok_or(Error::ServerNotFound(server_uuid))?;
 
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
@@ -264,7 +379,7 @@ async fn send_otp(
         Ok(())
     };
     debug!("send_otp");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -279,10 +394,10 @@ async fn get_account_info(
         let server = state
             .servers
             .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?;
+            .ok_or(Error::ServerNotFound(server_uuid))?;
 
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
@@ -290,7 +405,7 @@ async fn get_account_info(
         Ok(info)
     };
     debug!("get_account_info");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -306,10 +421,10 @@ async fn update_email(
         let server = state
             .servers
             .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?;
+            .ok_or(Error::ServerNotFound(server_uuid))?;
 
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
@@ -317,7 +432,7 @@ async fn update_email(
         Ok(())
     };
     debug!("update_email");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -333,10 +448,10 @@ async fn update_password(
         let server = state
             .servers
             .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?;
+            .ok_or(Error::ServerNotFound(server_uuid))?;
 
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
@@ -344,41 +459,97 @@ async fn update_password(
         Ok(())
     };
     debug!("update_password");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
-async fn get_session(app_handle: tauri::AppHandle, server_uuid: Uuid) -> CommandResult<Session> {
-    let internal = async {
-        let state = app_handle.state::<Mutex<AppState>>();
-        let mut state = state.lock().await;
-        let server = state
-            .servers
-            .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?;
+async fn get_session(
+    app_handle: tauri::AppHandle,
+    server_uuid: Uuid,
+    username: Option<String>,
+) -> CommandResult<Session> {
+    debug!("get_session");
+    get_session_for_account(&app_handle, server_uuid, username.as_deref()).await
+}
 
-        let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
-        };
+/// Gets a session for a saved account, or for the selected account if no username is given.
+/// A one-time (not remembered) login takes priority over the selected account.
+async fn get_session_for_account(
+    app_handle: &tauri::AppHandle,
+    server_uuid: Uuid,
+    username: Option<&str>,
+) -> Result<Session> {
+    let _state = app_handle.state::<Mutex<AppState>>();
+    let mut state = _state.lock().await;
+    let server = state
+        .servers
+        .get_entry(server_uuid)
+        .ok_or(Error::ServerNotFound(server_uuid))?;
+
+    let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
+        return Err(Error::NotEndpointServer);
+    };
 
-        // We first check the temp tokens, then the saved tokens.
-        // Temp tokens are one-time use, so they are removed here
-        let token = match state.temp_tokens.remove(&server_uuid) {
-            Some(token) => Some(token),
-            None => state.tokens.get_token(server_uuid),
-        };
+    // We first check the temp tokens, then the saved tokens.
+    // Temp tokens are one-time use, so they are removed here
+    let temp_token = match username {
+        Some(_) => None,
+        None => state.temp_tokens.remove(&server_uuid),
+    };
+    let (refresh_token, saved_as) = match temp_token {
+        Some(token) => (token, None),
+        None => {
+            let account = state
+                .tokens
+                .get_token(server_uuid, username)
+                .ok_or(Error::NotLoggedIn)?;
+            let username = account.get_username().to_string();
+            (account.get_refresh_token().clone(), Some(username))
+        }
+    };
 
-        let Some(refresh_token) = token else {
-            return Err("Not logged in".into());
-        };
-        let refresh_token = refresh_token.to_string();
-        drop(state);
+    if refresh_token.is_expired() {
+        // no point asking the server; forget the stale token so the user logs in again
+        if let Some(account) = &saved_as {
+            state.tokens.remove_token(server_uuid, Some(account));
+            state.save();
+        }
+        return Err(Error::SessionExpired);
+    }
+    drop(state);
 
-        let session = endpoint::get_session(&refresh_token, &endpoint).await?;
-        Ok(session)
+    let res = endpoint::get_session(refresh_token.get_token(), &endpoint).await;
+    let mut state = _state.lock().await;
+    let mut session = match res {
+        Ok(session) => session,
+        Err(Error::SessionExpired) => {
+            if let Some(account) = &saved_as {
+                state.tokens.remove_token(server_uuid, Some(account));
+                state.save();
+            }
+            return Err(Error::SessionExpired);
+        }
+        Err(e) => return Err(e),
     };
-    debug!("get_session");
-    internal.await.map_err(|e: Error| e.to_string())
+
+    let Some(mut account) = saved_as else {
+        return Ok(session);
+    };
+
+    // Tokens migrated from before multiple accounts were supported don't have a username
+    if account.is_empty() {
+        account = session.get_username().to_string();
+        state.tokens.rename_account(server_uuid, "", &account);
+        state.save();
+    }
+
+    // Rotate the saved refresh token if the server handed us a new one
+    if let Some(new_token) = session.take_refresh_token() {
+        debug!("Rotating refresh token for server {}", server_uuid);
+        state.tokens.save_token(server_uuid, &account, new_token);
+        state.save();
+    }
+    Ok(session)
 }
 
 #[tauri::command]
@@ -387,348 +558,34 @@ async fn prep_launch(
     server_uuid: Uuid,
     version_uuid: Uuid,
     session_token: Option<String>,
+    username: Option<String>,
 ) -> CommandResult<Option<usize>> {
     let internal = async {
-        // timeout in seconds, for giving the user time
-        // to see any warnings before launch
-        let mut timeout_sec = None;
-
-        let app_statics = get_app_statics();
-        let working_dir = &app_statics.resource_dir;
-        let mut ffrunner_path = working_dir.clone();
-        ffrunner_path.push("ffrunner.exe");
-        let mut cmd = std::process::Command::new(ffrunner_path.clone());
-        cmd.current_dir(working_dir);
-
-        let state = app_handle.state::<Mutex<AppState>>();
-        let mut state = state.lock().await;
-        let server = state
-            .servers
-            .get_entry(server_uuid)
-            .ok_or(format!("Server {} not found", server_uuid))?
-            .clone();
-
-        let mut server_name = server.get_description();
-        let addr;
-        let mut versions = Vec::new();
-        let mut custom_loading_screen = false;
-        let mut custom_icon_url = None;
-        match &server.info {
-            ServerInfo::Simple { ip, version } => {
-                addr = ip.clone();
-                versions.push(version.clone());
-            }
-            ServerInfo::Endpoint { endpoint, .. } => {
-                // Ask the endpoint server for the UUID of the current version
-                let Ok(api_info) = endpoint::get_info(endpoint).await else {
-                    return Err("Failed to contact API server".into());
-                };
-
-                if api_info.custom_loading_screen.is_some_and(|b| b) {
-                    custom_loading_screen = true;
-                }
-
-                if let Ok(icon_url) = endpoint::get_custom_icon_url(endpoint).await {
-                    custom_icon_url = icon_url;
-                }
-
-                server_name = Some(format!("\"{}\"", api_info.server_name));
-                addr = api_info.login_address.clone();
-                versions = api_info.get_supported_versions();
+        // launching as a specific saved account; log in with it here
+        let session_token = match username {
+            Some(username) => {
+                let session =
+                    get_session_for_account(&app_handle, server_uuid, Some(&username)).await?;
+                Some(session.get_session_token().to_string())
             }
-        }
-
-        // Ensure the version is supported
-        if !versions.contains(&version_uuid.to_string()) {
-            return Err(format!("Version {} not supported by server", version_uuid).into());
-        }
-
-        let ip = util::resolve_server_addr(&addr)?;
-
-        let Some(version) = state.versions.get_entry(version_uuid) else {
-            return Err(format!("Version {} not found", version_uuid).into());
+            None => session_token,
         };
 
-        let base_cache_dir = &state.config.launcher.game_cache_path;
-        let cache_dir = util::get_cache_dir_for_version(base_cache_dir, version);
-        if !cache_dir.exists() {
-            // check for cache upgrade
-            if let Some(parent_uuid) = version.get_parent_uuid() {
-                if let Some(parent_version) = state.versions.get_entry(parent_uuid) {
-                    let parent_cache_dir =
-                        util::get_cache_dir_for_version(base_cache_dir, parent_version);
-                    if parent_cache_dir.exists() {
-                        if let Err(e) = util::copy_dir(&parent_cache_dir, &cache_dir) {
-                            warn!(
-                                "Failed to upgrade cache from parent version {} for {}: {}",
-                                parent_uuid, version_uuid, e
-                            );
-                        } else {
-                            info!(
-                                "Upgraded cache from parent version {} for {}",
-                                parent_uuid, version_uuid
-                            );
-
-                            if state.config.launcher.delete_old_game_caches
-                                && state.get_version_use_count(parent_uuid) == 0
-                            {
-                                if let Err(e) = util::delete_dir(&parent_cache_dir) {
-                                    warn!(
-                                        "Failed to delete cache for parent version {}: {}",
-                                        parent_uuid, e
-                                    );
-                                } else {
-                                    info!("Deleted cache for parent version {}", parent_uuid);
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        let _ = std::fs::create_dir_all(&cache_dir);
-        cmd.env("UNITY_FF_CACHE_DIR", cache_dir);
-
-        let mut asset_url = version.get_asset_url();
-        let mut main_url = version
-            .get_main_file_url()
-            .unwrap_or(format!("{}/main.unity3d", asset_url));
-
-        // use offline cache if available
-        let base_offline_cache_dir = &state.config.launcher.offline_cache_path;
-        let offline_cache_dir = util::get_cache_dir_for_version(base_offline_cache_dir, version);
-        if state.config.launcher.use_offline_caches && offline_cache_dir.exists() {
-            let offline_asset_url = util::get_path_as_file_uri(&offline_cache_dir);
-            let offline_cache_dir = offline_cache_dir.to_string_lossy().to_string();
-            let offline_main_url = format!("{}\\main.unity3d", offline_cache_dir);
-
-            let use_offline_cache = if state.config.launcher.verify_offline_caches {
-                match version
-                    .validate_compressed_stop_on_first_fail(&offline_cache_dir, None)
-                    .await
-                {
-                    Ok(corrupted) => {
-                        let is_corrupt = corrupted.is_some();
-                        if is_corrupt {
-                            let msg = format!(
-                                "Offline cache for version {} is corrupt. Not using.",
-                                util::get_version_name(version)
-                            );
-                            warn!("{}", msg);
-                            util::send_alert(app_handle.clone(), AlertVariant::Warning, &msg);
-                            timeout_sec = Some(3);
-                        }
-                        !is_corrupt
-                    }
-                    Err(e) => {
-                        let msg = format!(
-                            "Failed to validate offline cache for version {}: {:?}",
-                            util::get_version_name(version),
-                            e
-                        );
-                        error!("{}", msg);
-                        util::send_alert(app_handle.clone(), AlertVariant::Error, &msg);
-                        false
-                    }
-                }
-            } else {
-                true
-            };
-
-            if use_offline_cache {
-                let msg = format!(
-                    "Using offline cache for version {}",
-                    util::get_version_name(version)
-                );
-                info!("{}", msg);
-                util::send_alert(app_handle.clone(), AlertVariant::Success, &msg);
-
-                asset_url = offline_asset_url;
-                main_url = offline_main_url;
-            }
-        } else if state.config.launcher.proxy_asset_downloads {
-            let mut proxy = TcpProxy::default();
-            proxy.set_base_path(asset_url.clone());
-            let listener = TcpListener::bind("127.0.0.1:0").await?;
-            let proxy_addr = listener.local_addr()?;
-            let new_asset_url = format!("http://{}", proxy_addr);
-            asset_url = new_asset_url;
-
-            let handle = tokio::spawn(async move {
-                proxy.run(&listener).await;
-            });
-            state.proxy = Some(handle);
-        }
-
-        // Upgrade the main URL to HTTPS, if it's available, since ffrunner supports it
-        if main_url.starts_with("http://") {
-            let main_url_upgraded = main_url.replacen("http://", "https://", 1);
-            if util::does_web_file_exist(&main_url_upgraded).await {
-                main_url = main_url_upgraded;
-            } else if !util::does_web_file_exist(&main_url).await {
-                return Err(format!("Main file not found: {}", main_url).into());
-            }
-        }
-
-        debug!("Asset URL: {}", asset_url);
-        debug!("Main URL: {}", main_url);
-
-        let log_file_path = format!(
-            "\"{}\"",
-            app_statics
-                .ffrunner_log_path
-                .clone()
-                .to_str()
-                .ok_or("Invalid log file path")?
-        );
-
-        cmd.args(["-m", &main_url])
-            .args(["-a", &ip])
-            .args(["--asseturl", &format!("{}/", asset_url)])
-            .args(["-l", &log_file_path]);
-
-        if let Some(server_name) = server_name {
-            // window title
-            cmd.args(["-n", &server_name]);
-        }
-
-        if let Some(icon_url) = custom_icon_url {
-            // window icon
-            cmd.args(["-i", &icon_url]);
-        }
-
-        if let ServerInfo::Endpoint { endpoint, .. } = &server.info {
-            match session_token {
-                None => {
-                    warn!("No session token provided for endpoint server");
-                }
-                Some(token) => {
-                    let (username, cookie) = endpoint::get_cookie(&token, endpoint).await?;
-                    cmd.args(["-u", &username]).args(["-t", &cookie]);
-                }
-            };
-
-            cmd.args(["-e", endpoint]);
-
-            if custom_loading_screen {
-                cmd.arg("--loader-images");
-            }
-        }
-
-        // Window size
-        if let Some(window_size) = &state.config.game.window_size {
-            cmd.args([
-                "--width",
-                &window_size.width.to_string(),
-                "--height",
-                &window_size.height.to_string(),
-            ]);
-        }
-
-        // Graphics API overrides
-        match state.config.game.graphics_api {
-            config::GraphicsApi::Dx9 => {}
-            config::GraphicsApi::OpenGl => {
-                cmd.arg("--force-opengl");
-            }
-            config::GraphicsApi::Vulkan => {
-                cmd.arg("--force-vulkan");
-                #[cfg(debug_assertions)]
-                cmd.env("DXVK_HUD", "1");
-            }
-        }
-
-        // FPS behavior
-        match state.config.game.fps_fix {
-            config::FpsFix::On => {}
-            config::FpsFix::OnWithLimiter(limit) => {
-                cmd.env("UNITY_FF_FPS_CAP", limit.to_string());
-            }
-            config::FpsFix::Off => {
-                cmd.env("UNITY_FF_FPS_CAP", "old");
-            }
-        }
-
-        #[cfg(debug_assertions)]
-        cmd.arg("-v"); // verbose logging
-
-        if !state.launch_profiles.has_entries() {
-            return Err("No launch profiles found in game settings. Please create one.".into());
-        }
-
-        let selected_launch_profile = state.config.game.launch_profile;
-        let profile = state
-            .launch_profiles
-            .get(selected_launch_profile)
-            .ok_or(format!(
-                "Launch profile '{}' not found",
-                selected_launch_profile
-            ))?;
-
-        let launch_fmt = profile.get_command();
-        cmd = util::gen_launch_command(cmd, launch_fmt);
-
-        #[cfg(not(target_os = "windows"))]
-        {
-            // Prefix setup
-            let mut compat_data_dir = util::get_compat_data_dir(&cmd);
-            if compat_data_dir.is_none() {
-                // not specified; use launcher compat data dir
-                let mut launcher_compat_dir = app_statics.compat_data_dir.clone();
-                launcher_compat_dir.push(profile.get_id().to_string());
-
-                if cmd.get_program().to_string_lossy().ends_with("proton") {
-                    cmd.env(
-                        "STEAM_COMPAT_DATA_PATH",
-                        launcher_compat_dir.to_string_lossy().to_string(),
-                    );
-                    // proton sets WINEPREFIX internally
-                } else {
-                    // assume wine
-                    cmd.env(
-                        "WINEPREFIX",
-                        launcher_compat_dir.to_string_lossy().to_string(),
-                    );
-                }
-                compat_data_dir = Some(launcher_compat_dir);
-            }
-
-            let compat_data_dir = compat_data_dir.unwrap();
-            if !compat_data_dir.exists() {
-                debug!("Creating prefix at {}", compat_data_dir.to_string_lossy());
-                std::fs::create_dir_all(&compat_data_dir)?;
-            }
-        }
-
-        // Detach stdio so the child doesn't crash from broken pipes
-        // when the launcher exits (e.g. LaunchBehavior::Quit)
-        cmd.stdin(Stdio::null());
-        cmd.stdout(Stdio::null());
-        cmd.stderr(Stdio::null());
-
-        util::log_command(&cmd);
-        state.launch_cmd = Some(cmd);
-        Ok(timeout_sec)
+        let alerts = AlertSink::App(app_handle.clone());
+        let state = app_handle.state::<Mutex<AppState>>();
+        launch::prep_launch(&state, &alerts, server_uuid, version_uuid, session_token).await
     };
     debug!(
         "prep_launch server {} version {}",
         server_uuid, version_uuid
     );
-    internal
-        .await
-        .map_err(|e: Box<dyn std::error::Error>| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
 async fn validate_cache(app_handle: tauri::AppHandle, uuid: Uuid, offline: bool) {
     let internal = async {
         let _permit = VALIDATION_SEMAPHORE.acquire().await?;
-        let ops = if offline {
-            &OFFLINE_CACHE_OPS
-        } else {
-            &GAME_CACHE_OPS
-        };
 
         let (tx, rx) = mpsc::channel();
         let tx_clone = tx.clone();
@@ -742,7 +599,7 @@ async fn validate_cache(app_handle: tauri::AppHandle, uuid: Uuid, offline: bool)
         let version = state
             .versions
             .get_entry(uuid)
-            .ok_or("Version not found")?
+            .ok_or(Error::VersionNotFound(uuid))?
             .clone();
         let path = if offline {
             util::get_cache_dir_for_version(&state.config.launcher.offline_cache_path, &version)
@@ -751,35 +608,40 @@ async fn validate_cache(app_handle: tauri::AppHandle, uuid: Uuid, offline: bool)
         };
         drop(state);
 
-        {
-            let mut ops = ops.get_or_init(|| Mutex::new(HashSet::new())).lock().await;
-            if ops.contains(&uuid) {
-                return Err("Cache operation in progress".into());
-            }
-            ops.insert(uuid);
-        }
+        let token = cache::begin_op(uuid, offline).await?;
 
         tauri::async_runtime::spawn_blocking(move || {
-            util::cache_progress_loop(offline, app_handle, rx, uuid);
+            let items =
+                util::cache_progress_loop(offline, app_handle.clone(), rx, uuid, HashMap::new());
+            record_validation_report(&app_handle, uuid, offline, items);
         });
 
         tauri::async_runtime::spawn(async move {
             let _permit = _permit; // move into the async block
+            let mut cancelled = false;
             if !util::is_dir_empty(&path).unwrap_or(true) {
                 let path = path.to_string_lossy().to_string();
-                if offline {
-                    let _ = version.validate_compressed(&path, Some(cb)).await;
-                } else {
-                    let _ = version.validate_uncompressed(&path, Some(cb)).await;
+                let validate = async {
+                    if offline {
+                        version.validate_compressed(&path, Some(cb)).await
+                    } else {
+                        version.validate_uncompressed(&path, Some(cb)).await
+                    }
+                };
+                tokio::select! {
+                    _ = token.cancelled() => cancelled = true,
+                    _ = validate => {}
                 }
             }
 
-            {
-                let mut ops = ops.get_or_init(|| Mutex::new(HashSet::new())).lock().await;
-                ops.remove(&uuid);
-            }
+            cache::end_op(uuid, offline).await;
 
-            if let Err(e) = tx.send(CacheEvent::Done) {
+            let event = if cancelled {
+                CacheEvent::Cancelled
+            } else {
+                CacheEvent::Done
+            };
+            if let Err(e) = tx.send(event) {
                 error!("Failed to send cache done event: {}", e);
             }
         });
@@ -801,34 +663,40 @@ async fn download_cache(
 ) -> CommandResult<()> {
     let internal = async {
         let _permit = DOWNLOAD_SEMAPHORE.acquire().await?;
-        let ops = if offline {
-            &OFFLINE_CACHE_OPS
-        } else {
-            &GAME_CACHE_OPS
-        };
-
-        if !offline {
-            return Err("Not implemented".into());
-        }
 
         let state = app_handle.state::<Mutex<AppState>>();
         let state = state.lock().await;
         let version = state
             .versions
             .get_entry(uuid)
-            .ok_or("Version not found")?
+            .ok_or(Error::VersionNotFound(uuid))?
             .clone();
+        let mirrors = state.versions.get_mirrors(uuid).to_vec();
+        let offline_path =
+            util::get_cache_dir_for_version(&state.config.launcher.offline_cache_path, &version);
         let path = if offline {
-            util::get_cache_dir_for_version(&state.config.launcher.offline_cache_path, &version)
+            offline_path.clone()
         } else {
             util::get_cache_dir_for_version(&state.config.launcher.game_cache_path, &version)
         };
+        let report = state.validation_reports.get(uuid, offline).cloned();
         drop(state);
 
         std::fs::create_dir_all(&path)?;
-        if !repair && !util::is_dir_empty(&path)? {
-            return Err("Cache directory not empty".into());
-        }
+        // a cache that already has files in it is a download that got cancelled or cut off.
+        // repairing only fetches files that are missing or incomplete, so finished files are kept.
+        // a file that was cut off partway is downloaded again from the start.
+        let repair = repair || !util::is_dir_empty(&path)?;
+
+        // a repair only needs to touch what the last validation flagged, if it's trustworthy.
+        // the rest of that report is shown as-is, with the repaired files updated as they're done.
+        let (targets, seed) = match report.filter(|_| repair) {
+            Some(report) => match get_repair_targets(&version, offline, &report) {
+                Some(targets) => (Some(targets), report.get_items().clone()),
+                None => (None, HashMap::new()),
+            },
+            None => (None, HashMap::new()),
+        };
 
         let (tx, rx) = mpsc::channel();
         let tx_clone = tx.clone();
@@ -837,40 +705,204 @@ async fn download_cache(
         };
         let cb = Arc::new(cb);
 
-        {
-            let mut ops = ops.get_or_init(|| Mutex::new(HashSet::new())).lock().await;
-            if ops.contains(&uuid) {
-                return Err("Cache operation in progress".into());
-            }
-            ops.insert(uuid);
-        }
+        let token = cache::begin_op(uuid, offline).await?;
 
+        let alert_handle = app_handle.clone();
         tauri::async_runtime::spawn_blocking(move || {
-            util::cache_progress_loop(offline, app_handle, rx, uuid);
+            let items = util::cache_progress_loop(offline, app_handle.clone(), rx, uuid, seed);
+            record_validation_report(&app_handle, uuid, offline, items);
         });
 
-        let path = path.to_string_lossy().to_string();
         tauri::async_runtime::spawn(async move {
             let _permit = _permit; // move into the async block
-            if repair {
-                let _ = version.repair(&path, Some(cb)).await;
+            let mut cancelled = false;
+            if offline {
+                let dir = path.clone();
+                let path = path.to_string_lossy().to_string();
+                let download = async {
+                    if let Some(targets) = &targets {
+                        let files = util::get_manifest_file_infos(&version);
+                        let on_file = |name: &str, fixed: bool| {
+                            let item = CacheProgressItem {
+                                item_size: files.get(name).map_or(0, |info| info.size),
+                                corrupt: !fixed,
+                                missing: !fixed && !dir.join(name).exists(),
+                            };
+                            let event = CacheEvent::ItemProcessed(name.to_string(), item);
+                            if let Err(e) = tx.send(event) {
+                                error!("Failed to send cache progress event: {}", e);
+                            }
+                        };
+                        proxy::repair_files(&version, &mirrors, &dir, targets, on_file).await;
+                        return Ok(());
+                    }
+
+                    let (version, _proxy) = proxy::proxy_version(&version, &mirrors).await?;
+                    let res = if repair {
+                        version.repair(&path, Some(cb)).await.map(|_| ())
+                    } else {
+                        version.download_compressed(&path, Some(cb)).await
+                    };
+                    res.map_err(|e| Error::from(format!("{:?}", e)))
+                };
+                tokio::select! {
+                    _ = token.cancelled() => cancelled = true,
+                    res = download => {
+                        if let Err(e) = res {
+                            warn!("Offline cache download for {} failed: {}", uuid, e);
+                        }
+                    }
+                }
             } else {
-                let _ = version.download_compressed(&path, Some(cb)).await;
+                // the game cache is unpacked from bundles, so repairing is the same as downloading
+                match cache::unpack_game_cache(
+                    &version,
+                    &path,
+                    &offline_path,
+                    &mirrors,
+                    targets.as_deref(),
+                    &token,
+                )
+                .await
+                {
+                    Ok(()) => {
+                        // after a targeted repair, only the bundles that were unpacked again
+                        // need another look
+                        let to_validate = match &targets {
+                            Some(targets) => util::with_only_bundles(&version, targets)
+                                .unwrap_or_else(|_| version.clone()),
+                            None => version.clone(),
+                        };
+                        let path = path.to_string_lossy().to_string();
+                        tokio::select! {
+                            _ = token.cancelled() => cancelled = true,
+                            _ = to_validate.validate_uncompressed(&path, Some(cb)) => {}
+                        }
+                        // filling the cache ahead of time counts as using it,
+                        // so it isn't first in line for eviction
+                        let state = alert_handle.state::<Mutex<AppState>>();
+                        let mut state = state.lock().await;
+                        state.cache_usage.touch(uuid);
+                        if let Err(e) = state.cache_usage.save() {
+                            warn!("Failed to save cache usage: {}", e);
+                        }
+                    }
+                    Err(Error::Cancelled) => cancelled = true,
+                    Err(e) => {
+                        let msg = format!(
+                            "Failed to download game cache for {}: {}",
+                            util::get_version_name(&version),
+                            e
+                        );
+                        error!("{}", msg);
+                        util::send_alert(alert_handle, AlertVariant::Error, &msg);
+                    }
+                }
             }
 
-            {
-                let mut ops = ops.get_or_init(|| Mutex::new(HashSet::new())).lock().await;
-                ops.remove(&uuid);
-            }
+            cache::end_op(uuid, offline).await;
 
-            if let Err(e) = tx.send(CacheEvent::Done) {
+            let event = if cancelled {
+                info!("Cancelled download of {}", util::get_version_name(&version));
+                CacheEvent::Cancelled
+            } else {
+                CacheEvent::Done
+            };
+            if let Err(e) = tx.send(event) {
                 error!("Failed to send cache done event: {}", e);
             }
         });
         Ok(())
     };
     debug!("download_cache {} {} {}", uuid, offline, repair);
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+/// Saves what a cache operation that ran to the end found as that cache's validation report
+fn record_validation_report(
+    app_handle: &tauri::AppHandle,
+    uuid: Uuid,
+    offline: bool,
+    items: Option<HashMap<String, CacheProgressItem>>,
+) {
+    // a cancelled run only saw part of the cache
+    let Some(items) = items else {
+        return;
+    };
+    let state = app_handle.state::<Mutex<AppState>>();
+    let mut state = state.blocking_lock();
+    if items.is_empty() {
+        // nothing was there to check
+        if !state.validation_reports.remove(uuid, offline) {
+            return;
+        }
+    } else {
+        state
+            .validation_reports
+            .set(uuid, offline, ValidationReport::new(items));
+    }
+    if let Err(e) = state.validation_reports.save() {
+        warn!("Failed to save validation reports: {}", e);
+    }
+}
+
+/// What a repair has to fix according to a validation report: offline cache files, or bundles
+/// for the game cache. None if the report doesn't account for the whole cache or flags nothing,
+/// in which case everything should be repaired.
+fn get_repair_targets(
+    version: &Version,
+    offline: bool,
+    report: &ValidationReport,
+) -> Option<Vec<String>> {
+    let flagged = report.get_flagged();
+    if flagged.is_empty() {
+        return None;
+    }
+
+    if offline {
+        // downloads that stopped early only report the files they got to
+        let files = util::get_manifest_file_infos(version);
+        let complete = !files.is_empty()
+            && files
+                .keys()
+                .all(|name| report.get_items().contains_key(name));
+        return complete.then_some(flagged);
+    }
+
+    // game cache files live in a directory named after the bundle they came from
+    let bundle_names = util::get_manifest_bundle_names(version);
+    let mut targets: Vec<String> = Vec::new();
+    for name in flagged {
+        let bundle = name.split(['/', '\\']).next().unwrap_or_default();
+        if !bundle_names.iter().any(|b| b == bundle) {
+            return None;
+        }
+        if !targets.iter().any(|t| t == bundle) {
+            targets.push(bundle.to_string());
+        }
+    }
+    Some(targets)
+}
+
+#[tauri::command]
+async fn get_validation_report(
+    app_handle: tauri::AppHandle,
+    uuid: Uuid,
+    offline: bool,
+) -> Option<ValidationReport> {
+    debug!("get_validation_report {} {}", uuid, offline);
+    let state = app_handle.state::<Mutex<AppState>>();
+    let state = state.lock().await;
+    state.validation_reports.get(uuid, offline).cloned()
+}
+
+/// Stops a running download, validation or repair. Files that finished downloading are kept
+/// for next time; the one that was in progress starts over.
+#[tauri::command]
+async fn cancel_cache_op(uuid: Uuid, offline: bool) -> CommandResult<bool> {
+    let internal = async { Ok(cache::cancel_op(uuid, offline).await) };
+    debug!("cancel_cache_op {} {}", uuid, offline);
+    internal.await
 }
 
 #[tauri::command]
@@ -880,39 +912,265 @@ async fn delete_cache(
     offline: bool,
 ) -> CommandResult<()> {
     let internal = async {
-        let ops = if offline {
-            &OFFLINE_CACHE_OPS
-        } else {
-            &GAME_CACHE_OPS
-        };
-
         let state = app_handle.state::<Mutex<AppState>>();
-        let state = state.lock().await;
-        let version = state.versions.get_entry(uuid).ok_or("Version not found")?;
+        let mut state = state.lock().await;
+        let version = state
+            .versions
+            .get_entry(uuid)
+            .ok_or(Error::VersionNotFound(uuid))?;
         let path = if offline {
             util::get_cache_dir_for_version(&state.config.launcher.offline_cache_path, version)
         } else {
             util::get_cache_dir_for_version(&state.config.launcher.game_cache_path, version)
         };
 
-        {
-            let mut ops = ops.get_or_init(|| Mutex::new(HashSet::new())).lock().await;
-            if ops.contains(&uuid) {
-                return Err("Cache operation in progress".into());
+        let _token = cache::begin_op(uuid, offline).await?;
+        let result = std::fs::remove_dir_all(&path);
+        cache::end_op(uuid, offline).await;
+        cache::discard_validation_report(&mut state, uuid, offline);
+        result?;
+        Ok(())
+    };
+    debug!("delete_cache {} {}", uuid, offline);
+    internal.await
+}
+
+#[tauri::command]
+async fn export_offline_cache(
+    app_handle: tauri::AppHandle,
+    uuid: Uuid,
+    path: String,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let state = state.lock().await;
+        let version = state
+            .versions
+            .get_entry(uuid)
+            .ok_or(Error::VersionNotFound(uuid))?
+            .clone();
+        let mirrors = state.versions.get_mirrors(uuid).to_vec();
+        let cache_dir =
+            util::get_cache_dir_for_version(&state.config.launcher.offline_cache_path, &version);
+        drop(state);
+
+        // keep downloads, repairs and deletes off the cache while it's being read
+        let _token = cache::begin_op(uuid, true).await?;
+        let dest = PathBuf::from(&path);
+        let result = tauri::async_runtime::spawn_blocking(move || {
+            let original = SignedManifest::load(&version)?;
+            archive::export_offline_cache(&version, &original, &mirrors, &cache_dir, &dest)
+        })
+        .await
+        .map_err(|e| e.to_string());
+        cache::end_op(uuid, true).await;
+        let size = result??;
+        info!("Exported offline cache for {} ({} bytes)", uuid, size);
+        Ok(())
+    };
+    debug!("export_offline_cache {} {}", uuid, path);
+    internal.await
+}
+
+#[tauri::command]
+async fn import_offline_cache(app_handle: tauri::AppHandle, path: String) -> CommandResult<String> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let state_guard = state.lock().await;
+        let base_offline_cache_dir = state_guard.config.launcher.offline_cache_path.clone();
+        let verifier = state_guard.get_manifest_verifier();
+        drop(state_guard);
+
+        // unpack next to the real caches so moving it into place is a rename.
+        // the staging directory gets an operation of its own, so it isn't cleaned up from under us
+        let import_id = Uuid::new_v4();
+        let staging_dir =
+            cache::get_import_staging_dir(Path::new(&base_offline_cache_dir), import_id);
+        let _token = cache::begin_op(import_id, true).await?;
+        let result = import_offline_cache_staged(
+            &app_handle,
+            &path,
+            &staging_dir,
+            &base_offline_cache_dir,
+            &verifier,
+        )
+        .await;
+        match std::fs::remove_dir_all(&staging_dir) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                warn!("Failed to clean up {}: {}", staging_dir.display(), e);
             }
-            ops.insert(uuid);
+            _ => {}
         }
+        cache::end_op(import_id, true).await;
+        result
+    };
+    debug!("import_offline_cache {}", path);
+    internal.await
+}
 
-        let result = std::fs::remove_dir_all(&path);
+async fn import_offline_cache_staged(
+    app_handle: &tauri::AppHandle,
+    path: &str,
+    staging_dir: &Path,
+    base_offline_cache_dir: &str,
+    verifier: &signing::ManifestVerifier,
+) -> Result<String> {
+    let archive_path = PathBuf::from(path);
+    let unpack_dir = staging_dir.to_path_buf();
+    let unpacked = tauri::async_runtime::spawn_blocking(move || {
+        archive::unpack_offline_cache(&archive_path, &unpack_dir)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    let Ok(version) = unpacked.original.parse() else {
+        return Err("Invalid manifest in offline cache archive".into());
+    };
+    let uuid = version.get_uuid();
 
-        {
-            let mut ops = ops.get_or_init(|| Mutex::new(HashSet::new())).lock().await;
-            ops.remove(&uuid);
+    // a build we already know is checked against our own copy of its manifest
+    let state = app_handle.state::<Mutex<AppState>>();
+    let known = state.lock().await.versions.get_entry(uuid).cloned();
+    let (version, warning) = match known {
+        Some(known) => (known, None),
+        None => {
+            let warning = unpacked
+                .original
+                .check(verifier, &format!("Manifest in {}", path))?;
+            (version, warning)
         }
-        Ok(result?)
     };
-    debug!("delete_cache {} {}", uuid, offline);
-    internal.await.map_err(|e: Error| e.to_string())
+    if util::get_manifest_file_infos(&version).is_empty() {
+        return Err(
+            "This build's manifest doesn't list its files, so its cache can't be checked".into(),
+        );
+    }
+
+    let files_dir = archive::get_staged_files_dir(staging_dir);
+    std::fs::create_dir_all(&files_dir)?;
+    let corrupt = {
+        let _permit = VALIDATION_SEMAPHORE.acquire().await?;
+        version
+            .validate_compressed(&files_dir.to_string_lossy(), None)
+            .await
+            .map_err(|e| Error::from(format!("{:?}", e)))?
+    };
+    if !corrupt.is_empty() {
+        return Err(format!(
+            "Offline cache archive has {} missing or corrupt file(s), e.g. {}",
+            corrupt.len(),
+            corrupt[0]
+        )
+        .into());
+    }
+
+    let version_label = util::get_version_name(&version);
+    let dest = util::get_cache_dir_for_version(base_offline_cache_dir, &version);
+    let _token = cache::begin_op(uuid, true).await?;
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        match dest.exists() {
+            true => std::fs::remove_dir_all(&dest),
+            false => Ok(()),
+        }
+        .and_then(|_| std::fs::rename(&files_dir, &dest))
+    })
+    .await;
+    cache::end_op_discarding_report(&state, uuid, true).await;
+    result.map_err(|e| e.to_string())??;
+
+    let mut state = state.lock().await;
+    if state.versions.get_entry(uuid).is_none() {
+        util::import_versions(vec![version.clone()])?;
+        if let Err(e) = unpacked.original.save(uuid) {
+            warn!(
+                "Failed to keep original manifest for {}: {}",
+                version_label, e
+            );
+        }
+        state.versions.set_mirrors(uuid, unpacked.mirrors);
+        state.versions.add_entry(version, VersionOrigin::AppData);
+        state.save();
+    }
+    drop(state);
+    if let Some(warning) = warning {
+        util::send_alert(app_handle.clone(), AlertVariant::Warning, &warning);
+    }
+    info!("Imported offline cache for {}", version_label);
+    Ok(version_label)
+}
+
+#[tauri::command]
+async fn scan_caches(app_handle: tauri::AppHandle) -> CommandResult<CacheScanReport> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let inventory = CacheInventory::new(&*state.lock().await);
+        let report = tauri::async_runtime::spawn_blocking(move || inventory.scan())
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(report)
+    };
+    debug!("scan_caches");
+    internal.await
+}
+
+#[tauri::command]
+async fn collect_caches(
+    app_handle: tauri::AppHandle,
+    targets: Vec<CacheDirTarget>,
+) -> CacheCollectResult {
+    debug!("collect_caches {}", targets.len());
+    let state = app_handle.state::<Mutex<AppState>>();
+    let inventory = CacheInventory::new(&*state.lock().await);
+    inventory.collect(&state, targets).await
+}
+
+/// Dry run of the game cache quota: what launching the given server now would evict
+#[tauri::command]
+async fn get_cache_eviction_plan(
+    app_handle: tauri::AppHandle,
+    server_uuid: Option<Uuid>,
+) -> CommandResult<EvictionPlan> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let server = match server_uuid {
+            Some(uuid) => Some(
+                state
+                    .lock()
+                    .await
+                    .servers
+                    .get_entry(uuid)
+                    .ok_or(Error::ServerNotFound(uuid))?
+                    .clone(),
+            ),
+            None => None,
+        };
+
+        let protected_versions = match server.map(|s| s.info) {
+            Some(ServerInfo::Simple { version, .. }) => vec![version],
+            Some(ServerInfo::Endpoint {
+                endpoint,
+                preferred_version,
+            }) => match endpoint::get_info(&endpoint).await {
+                Ok(info) => info.get_supported_versions(),
+                Err(e) => {
+                    warn!("Failed to get info from {}: {}", endpoint, e);
+                    preferred_version.into_iter().collect()
+                }
+            },
+            None => Vec::new(),
+        };
+        let protected: HashSet<Uuid> = protected_versions
+            .iter()
+            .filter_map(|v| Uuid::parse_str(v).ok())
+            .collect();
+
+        let planner = EvictionPlanner::new(&*state.lock().await);
+        let plan = tauri::async_runtime::spawn_blocking(move || planner.plan(&protected))
+            .await
+            .map_err(|e| e.to_string())?;
+        Ok(plan)
+    };
+    debug!("get_cache_eviction_plan {:?}", server_uuid);
+    internal.await
 }
 
 #[tauri::command]
@@ -931,7 +1189,50 @@ async fn import_from_openfusionclient(app_handle: tauri::AppHandle) -> CommandRe
         })
     };
     debug!("import_from_openfusionclient");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+#[tauri::command]
+async fn export_servers(
+    app_handle: tauri::AppHandle,
+    uuids: Vec<Uuid>,
+    include_versions: bool,
+    path: String,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let state = state.lock().await;
+        let bundle = state.export_server_bundle(&uuids, include_versions)?;
+        let bundle_str = serde_json::to_string_pretty(&bundle)?;
+        std::fs::write(&path, bundle_str)?;
+        Ok(())
+    };
+    debug!("export_servers {:?} {} {}", uuids, include_versions, path);
+    internal.await
+}
+
+#[tauri::command]
+async fn import_servers(app_handle: tauri::AppHandle, path: String) -> CommandResult<ImportCounts> {
+    let internal = async {
+        let bundle_str = std::fs::read_to_string(&path)?;
+        let bundle: ServerBundle = serde_json::from_str(&bundle_str)?;
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        let (version_count, server_count, warnings) = state.import_server_bundle(bundle)?;
+        if version_count > 0 || server_count > 0 {
+            state.save();
+            status::request_poll(app_handle.clone());
+        }
+        for warning in warnings {
+            util::send_alert(app_handle.clone(), AlertVariant::Warning, &warning);
+        }
+        Ok(ImportCounts {
+            version_count,
+            server_count,
+        })
+    };
+    debug!("import_servers {}", path);
+    internal.await
 }
 
 #[tauri::command]
@@ -940,8 +1241,12 @@ async fn reload_state(app_handle: tauri::AppHandle) -> bool {
     let state = app_handle.state::<Mutex<AppState>>();
     let mut state = state.lock().await;
     let first_run = !get_app_statics().app_data_dir.exists();
-    *state = AppState::load(app_handle.clone());
+    *state = AppState::load(&AlertSink::App(app_handle.clone()));
     state.save();
+    status::request_poll(app_handle.clone());
+    directory::start_syncing(app_handle.clone());
+    verify::start_verifying(app_handle.clone());
+    deep_link::set_ready(&app_handle);
     first_run
 }
 
@@ -960,16 +1265,19 @@ async fn get_info_for_server(
     let internal = async {
         let state = app_handle.state::<Mutex<AppState>>();
         let state = state.lock().await;
-        let server = state.servers.get_entry(uuid).ok_or("Server not found")?;
+        let server = state
+            .servers
+            .get_entry(uuid)
+            .ok_or(Error::ServerNotFound(uuid))?;
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
         let info = endpoint::get_info(&endpoint).await?;
         Ok(info.clone())
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -981,16 +1289,19 @@ async fn get_announcements_for_server(
     let internal = async {
         let state = app_handle.state::<Mutex<AppState>>();
         let state = state.lock().await;
-        let server = state.servers.get_entry(uuid).ok_or("Server not found")?;
+        let server = state
+            .servers
+            .get_entry(uuid)
+            .ok_or(Error::ServerNotFound(uuid))?;
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
         let announcements = endpoint::get_announcements(&endpoint).await?;
         Ok(announcements)
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1002,16 +1313,58 @@ async fn get_player_count_for_server(
     let internal = async {
         let state = app_handle.state::<Mutex<AppState>>();
         let state = state.lock().await;
-        let server = state.servers.get_entry(uuid).ok_or("Server not found")?;
+        let server = state
+            .servers
+            .get_entry(uuid)
+            .ok_or(Error::ServerNotFound(uuid))?;
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         drop(state);
 
         let status = endpoint::get_status(&endpoint).await?;
         Ok(status.player_count)
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+#[tauri::command]
+async fn probe_login_server(app_handle: tauri::AppHandle, uuid: Uuid) -> CommandResult<u64> {
+    debug!("probe_login_server {}", uuid);
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let state = state.lock().await;
+        let server = state
+            .servers
+            .get_entry(uuid)
+            .ok_or(Error::ServerNotFound(uuid))?;
+        let info = server.info.clone();
+        let address_family = state.config.launcher.address_family;
+        drop(state);
+
+        let addr = match info {
+            ServerInfo::Simple { ip, .. } => ip,
+            ServerInfo::Endpoint { endpoint, .. } => {
+                endpoint::get_info(&endpoint).await?.login_address
+            }
+        };
+        let ip = util::resolve_server_addr(&addr, address_family).await?;
+        let latency = util::probe_game_addr(&ip).await?;
+        Ok(latency.as_millis() as u64)
+    };
+    internal.await
+}
+
+#[tauri::command]
+async fn get_server_statuses() -> HashMap<Uuid, ServerStatus> {
+    debug!("get_server_statuses");
+    status::get_cached_statuses()
+}
+
+#[tauri::command]
+async fn refresh_server_statuses(app_handle: tauri::AppHandle) {
+    debug!("refresh_server_statuses");
+    status::poll_all(&app_handle).await;
 }
 
 #[tauri::command]
@@ -1028,15 +1381,16 @@ async fn add_server(
         if let Some(version) = &details.version {
             let version_uuid = Uuid::parse_str(version)?;
             if state.versions.get_entry(version_uuid).is_none() {
-                return Err(format!("Version {} not found", version).into());
+                return Err(Error::VersionNotFound(version_uuid));
             }
         }
 
         let new_uuid = state.servers.add_entry(details);
         state.save();
+        status::request_poll(app_handle.clone());
         Ok(new_uuid)
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1051,9 +1405,10 @@ async fn update_server(
         let mut state = state.lock().await;
         state.servers.update_entry(server)?;
         state.save();
+        status::request_poll(app_handle.clone());
         Ok(())
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1066,17 +1421,180 @@ async fn delete_server(app_handle: tauri::AppHandle, uuid: Uuid) -> CommandResul
         Ok(())
     };
     debug!("delete_server {}", uuid);
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+#[tauri::command]
+async fn set_server_favorite(
+    app_handle: tauri::AppHandle,
+    uuid: Uuid,
+    favorite: bool,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.servers.set_favorite(uuid, favorite)?;
+        state.save();
+        Ok(())
+    };
+    debug!("set_server_favorite {} {}", uuid, favorite);
+    internal.await
+}
+
+#[tauri::command]
+async fn move_server(
+    app_handle: tauri::AppHandle,
+    uuid: Uuid,
+    folder: Option<Uuid>,
+    before: Option<Uuid>,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.servers.move_entry(uuid, folder, before)?;
+        state.save();
+        Ok(())
+    };
+    debug!("move_server {} {:?} {:?}", uuid, folder, before);
+    internal.await
+}
+
+#[tauri::command]
+async fn add_server_folder(app_handle: tauri::AppHandle, name: String) -> Uuid {
+    debug!("add_server_folder {}", name);
+    let state = app_handle.state::<Mutex<AppState>>();
+    let mut state = state.lock().await;
+    let uuid = state.servers.add_folder(&name);
+    state.save();
+    uuid
+}
+
+#[tauri::command]
+async fn rename_server_folder(
+    app_handle: tauri::AppHandle,
+    uuid: Uuid,
+    name: String,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.servers.rename_folder(uuid, &name)?;
+        state.save();
+        Ok(())
+    };
+    debug!("rename_server_folder {} {}", uuid, name);
+    internal.await
+}
+
+#[tauri::command]
+async fn move_server_folder(
+    app_handle: tauri::AppHandle,
+    uuid: Uuid,
+    before: Option<Uuid>,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.servers.move_folder(uuid, before)?;
+        state.save();
+        Ok(())
+    };
+    debug!("move_server_folder {} {:?}", uuid, before);
+    internal.await
+}
+
+#[tauri::command]
+async fn add_server_directory(
+    app_handle: tauri::AppHandle,
+    url: String,
+    public_key: String,
+) -> CommandResult<Uuid> {
+    let internal = async {
+        let url = url.trim();
+        let public_key = public_key.trim();
+        // make sure the list is reachable and properly signed before subscribing
+        let entries = directory::fetch_list(url, public_key).await?;
+
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        if state
+            .servers
+            .get_directories()
+            .iter()
+            .any(|d| d.get_url() == url)
+        {
+            return Err(format!("Already subscribed to {}", url).into());
+        }
+        let uuid = state.servers.add_directory(url, public_key);
+        state.servers.apply_directory(uuid, entries)?;
+        state.save();
+        status::request_poll(app_handle.clone());
+        Ok(uuid)
+    };
+    debug!("add_server_directory {}", url);
+    internal.await
+}
+
+#[tauri::command]
+async fn remove_server_directory(app_handle: tauri::AppHandle, uuid: Uuid) {
+    debug!("remove_server_directory {}", uuid);
+    let state = app_handle.state::<Mutex<AppState>>();
+    let mut state = state.lock().await;
+    state.servers.remove_directory(uuid);
+    state.save();
+}
+
+#[tauri::command]
+async fn sync_server_directories(app_handle: tauri::AppHandle) {
+    debug!("sync_server_directories");
+    directory::sync_all(&app_handle).await;
+}
+
+#[tauri::command]
+async fn restore_hidden_servers(app_handle: tauri::AppHandle) {
+    debug!("restore_hidden_servers");
+    let state = app_handle.state::<Mutex<AppState>>();
+    let mut state = state.lock().await;
+    state.servers.clear_hidden();
+    state.save();
+    drop(state);
+    directory::sync_all(&app_handle).await;
+}
+
+#[tauri::command]
+async fn delete_server_folder(app_handle: tauri::AppHandle, uuid: Uuid) {
+    debug!("delete_server_folder {}", uuid);
+    let state = app_handle.state::<Mutex<AppState>>();
+    let mut state = state.lock().await;
+    state.servers.remove_folder(uuid);
+    state.save();
 }
 
 #[tauri::command]
 async fn import_version(app_handle: tauri::AppHandle, uri: String) -> CommandResult<String> {
     let internal = async {
-        let Ok(version) = Version::from_manifest(&uri).await else {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let verifier = state.lock().await.get_manifest_verifier();
+
+        let is_remote = uri.starts_with("http://") || uri.starts_with("https://");
+        let (manifest, signature) = if is_remote {
+            let manifest = util::do_simple_get(&uri).await?;
+            // fetched even if we don't check it, so it can go along with the manifest
+            let signature = signing::fetch_signature(&uri).await;
+            (manifest, signature)
+        } else {
+            (
+                std::fs::read_to_string(&uri)?,
+                signing::read_signature(&uri),
+            )
+        };
+        let original = SignedManifest::new(manifest, signature);
+        let warning = original.check(&verifier, &format!("Manifest {}", uri))?;
+        let Ok(version) = original.parse() else {
             return Err("Invalid manifest".into());
         };
+        let mirrors = mirror::parse_manifest_mirrors(&original.manifest);
 
-        let state = app_handle.state::<Mutex<AppState>>();
         let mut state = state.lock().await;
 
         if state.versions.get_entry(version.get_uuid()).is_some() {
@@ -1088,11 +1606,60 @@ async fn import_version(app_handle: tauri::AppHandle, uri: String) -> CommandRes
             None => version.get_uuid().to_string(),
         };
         util::import_versions(vec![version.clone()])?;
-        state.versions.add_entry(version);
+        if let Err(e) = original.save(version.get_uuid()) {
+            warn!(
+                "Failed to keep original manifest for {}: {}",
+                version_label, e
+            );
+        }
+        state.versions.set_mirrors(version.get_uuid(), mirrors);
+        state.versions.add_entry(version, VersionOrigin::AppData);
+        state.save();
+        if let Some(warning) = warning {
+            util::send_alert(app_handle.clone(), AlertVariant::Warning, &warning);
+        }
         Ok(version_label)
     };
     debug!("import_build");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+#[tauri::command]
+async fn get_trusted_keys(app_handle: tauri::AppHandle) -> Vec<TrustedKey> {
+    debug!("get_trusted_keys");
+    let state = app_handle.state::<Mutex<AppState>>();
+    let state = state.lock().await;
+    state.trust_store.get_keys().to_vec()
+}
+
+#[tauri::command]
+async fn add_trusted_key(
+    app_handle: tauri::AppHandle,
+    name: String,
+    public_key: String,
+) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.trust_store.add_key(&name, &public_key)?;
+        state.save();
+        Ok(())
+    };
+    debug!("add_trusted_key {} {}", name, public_key);
+    internal.await
+}
+
+#[tauri::command]
+async fn remove_trusted_key(app_handle: tauri::AppHandle, public_key: String) -> CommandResult<()> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let mut state = state.lock().await;
+        state.trust_store.remove_key(&public_key)?;
+        state.save();
+        Ok(())
+    };
+    debug!("remove_trusted_key {}", public_key);
+    internal.await
 }
 
 #[tauri::command]
@@ -1100,17 +1667,21 @@ async fn add_version_manual(
     app_handle: tauri::AppHandle,
     name: String,
     asset_url: String,
+    mirrors: Option<Vec<String>>,
 ) -> CommandResult<()> {
     let internal = async {
         let state = app_handle.state::<Mutex<AppState>>();
         let mut state = state.lock().await;
         let version = Version::build_barebones(&asset_url, Some(&name));
         util::import_versions(vec![version.clone()])?;
-        state.versions.add_entry(version);
+        let mirrors = mirror::normalize(mirrors.unwrap_or_default());
+        state.versions.set_mirrors(version.get_uuid(), mirrors);
+        state.versions.add_entry(version, VersionOrigin::AppData);
+        state.save();
         Ok(())
     };
     debug!("add_build_manual");
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1122,12 +1693,16 @@ async fn remove_version(
     let internal = async {
         let _state = app_handle.state::<Mutex<AppState>>();
         let state = _state.lock().await;
-        let version = state.versions.get_entry(uuid).ok_or("Version not found")?;
+        let version = state
+            .versions
+            .get_entry(uuid)
+            .ok_or(Error::VersionNotFound(uuid))?;
         let base_cache_dir = &state.config.launcher.game_cache_path;
         let cache_dir = util::get_cache_dir_for_version(base_cache_dir, version);
         let base_offline_cache_dir = &state.config.launcher.offline_cache_path;
         let offline_cache_dir = util::get_cache_dir_for_version(base_offline_cache_dir, version);
         util::remove_version(uuid, state.versions.get_file_names())?;
+        SignedManifest::remove(uuid);
         drop(state);
 
         let mut state = _state.lock().await;
@@ -1143,12 +1718,14 @@ async fn remove_version(
                     uuid
                 );
             }
+            cache::discard_validation_report(&mut state, uuid, false);
+            cache::discard_validation_report(&mut state, uuid, true);
         }
 
         Ok(())
     };
     debug!("remove_build {} {}", uuid, delete_caches);
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1160,7 +1737,10 @@ async fn open_folder_for_version(
     let internal = async {
         let state = app_handle.state::<Mutex<AppState>>();
         let state = state.lock().await;
-        let version = state.versions.get_entry(uuid).ok_or("Version not found")?;
+        let version = state
+            .versions
+            .get_entry(uuid)
+            .ok_or(Error::VersionNotFound(uuid))?;
         let base_cache_dir = if offline {
             &state.config.launcher.offline_cache_path
         } else {
@@ -1170,14 +1750,17 @@ async fn open_folder_for_version(
         drop(state);
 
         if cache_dir.exists() {
-            app_handle.shell().open(cache_dir.to_str().unwrap(), None)?;
+            app_handle
+                .shell()
+                .open(cache_dir.to_str().unwrap(), None)
+                .map_err(|e| e.to_string())?;
         } else {
             return Err("Cache directory does not exist".into());
         }
         Ok(())
     };
     debug!("open_folder_for_version {} {}", uuid, offline);
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1188,17 +1771,22 @@ async fn get_versions_for_server(
     let internal = async {
         let _state = app_handle.state::<Mutex<AppState>>();
         let state = _state.lock().await;
-        let server = state.servers.get_entry(uuid).ok_or("Server not found")?;
+        let server = state
+            .servers
+            .get_entry(uuid)
+            .ok_or(Error::ServerNotFound(uuid))?;
         let ServerInfo::Endpoint { endpoint, .. } = server.info.clone() else {
-            return Err("Server is not an endpoint server".into());
+            return Err(Error::NotEndpointServer);
         };
         let state_versions = state.versions.clone();
+        let verifier = state.get_manifest_verifier();
         drop(state);
 
         let info = endpoint::get_info(&endpoint).await?;
         let supported_versions = info.get_supported_versions();
         let mut new_versions = Vec::new();
         let mut supported_version_uuids = Vec::new();
+        let mut warnings = Vec::new();
         for version_uuid in supported_versions {
             let Ok(version_uuid) = Uuid::parse_str(&version_uuid) else {
                 warn!("Invalid version UUID: {}", version_uuid);
@@ -1211,25 +1799,221 @@ async fn get_versions_for_server(
                 continue;
             }
 
-            let Ok(version) = endpoint::fetch_version(&endpoint, version_uuid).await else {
-                warn!("Failed to fetch version {}", version_uuid);
-                continue;
-            };
-            new_versions.push(version);
+            match endpoint::fetch_version(&endpoint, version_uuid, &verifier).await {
+                Ok(fetched) => {
+                    warnings.extend(fetched.warning);
+                    new_versions.push((fetched.version, fetched.mirrors, fetched.original));
+                }
+                Err(e) => warn!("Failed to fetch version {}: {}", version_uuid, e),
+            }
         }
 
-        if let Err(e) = util::import_versions(new_versions.clone()) {
+        send_manifest_warnings(&app_handle, &warnings, &endpoint);
+
+        let to_import = new_versions.iter().map(|(v, _, _)| v.clone()).collect();
+        if let Err(e) = util::import_versions(to_import) {
             warn!("Failed to import versions for server {}: {}", uuid, e);
         }
 
         let mut state = _state.lock().await;
-        for version in new_versions {
-            state.versions.add_entry(version);
+        if !new_versions.is_empty() {
+            for (version, mirrors, original) in new_versions {
+                if let Err(e) = original.save(version.get_uuid()) {
+                    warn!(
+                        "Failed to keep original manifest for {}: {}",
+                        version.get_uuid(),
+                        e
+                    );
+                }
+                state.versions.set_mirrors(version.get_uuid(), mirrors);
+                state.versions.add_entry(version, VersionOrigin::Endpoint);
+            }
+            state.save();
         }
         Ok(supported_version_uuids)
     };
     debug!("get_versions_for_server {}", uuid);
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
+}
+
+/// Sends signature warnings for manifests fetched from an endpoint,
+/// as one alert per server rather than per version
+fn send_manifest_warnings(app_handle: &tauri::AppHandle, warnings: &[String], endpoint: &str) {
+    match warnings {
+        [] => {}
+        [warning] => util::send_alert(app_handle.clone(), AlertVariant::Warning, warning),
+        _ => util::send_alert(
+            app_handle.clone(),
+            AlertVariant::Warning,
+            &format!(
+                "{} version manifests from {} are not signed by a trusted key",
+                warnings.len(),
+                endpoint
+            ),
+        ),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct VersionUpdate {
+    uuid: Uuid,
+    name: String,
+    changes: Vec<String>,
+}
+
+/// Re-fetches the manifests of already-imported versions from the endpoint servers that
+/// list them, and replaces any that were republished. Only checks the given server if one is passed.
+#[tauri::command]
+async fn refresh_versions(
+    app_handle: tauri::AppHandle,
+    server_uuid: Option<Uuid>,
+) -> CommandResult<Vec<VersionUpdate>> {
+    let internal = async {
+        let _state = app_handle.state::<Mutex<AppState>>();
+        let state = _state.lock().await;
+        let endpoints: Vec<String> = match server_uuid {
+            Some(uuid) => {
+                let server = state
+                    .servers
+                    .get_entry(uuid)
+                    .ok_or(Error::ServerNotFound(uuid))?;
+                let ServerInfo::Endpoint { endpoint, .. } = &server.info else {
+                    return Err(Error::NotEndpointServer);
+                };
+                vec![endpoint.clone()]
+            }
+            None => state
+                .servers
+                .get_entries()
+                .iter()
+                .filter_map(|server| match &server.info {
+                    ServerInfo::Endpoint { endpoint, .. } => Some(endpoint.clone()),
+                    ServerInfo::Simple { .. } => None,
+                })
+                .collect(),
+        };
+        let state_versions = state.versions.clone();
+        let verifier = state.get_manifest_verifier();
+        drop(state);
+
+        let mut checked = HashSet::new();
+        let mut refreshed = Vec::new();
+        for endpoint in endpoints {
+            let info = match endpoint::get_info(&endpoint).await {
+                Ok(info) => info,
+                Err(e) if server_uuid.is_some() => return Err(e),
+                Err(e) => {
+                    warn!("Failed to get info from {}: {}", endpoint, e);
+                    continue;
+                }
+            };
+
+            let mut warnings = Vec::new();
+            for version_uuid in info.get_supported_versions() {
+                let Ok(version_uuid) = Uuid::parse_str(&version_uuid) else {
+                    warn!("Invalid version UUID: {}", version_uuid);
+                    continue;
+                };
+
+                // versions we don't have yet get picked up by get_versions_for_server
+                let Some(stored) = state_versions.get_entry(version_uuid) else {
+                    continue;
+                };
+                if state_versions.is_builtin(version_uuid) || checked.contains(&version_uuid) {
+                    continue;
+                }
+
+                match endpoint::fetch_version(&endpoint, version_uuid, &verifier).await {
+                    Ok(mut fetched) => {
+                        checked.insert(version_uuid);
+                        let mut changes = util::diff_versions(stored, &fetched.version)?;
+                        if fetched.mirrors != state_versions.get_mirrors(version_uuid) {
+                            changes.push("mirrors".to_string());
+                        }
+                        if changes.is_empty() {
+                            debug!("Version {} is up to date", version_uuid);
+                            continue;
+                        }
+                        warnings.extend(fetched.warning.take());
+                        refreshed.push((fetched, changes));
+                    }
+                    Err(e) => warn!("Failed to refresh version {}: {}", version_uuid, e),
+                }
+            }
+            send_manifest_warnings(&app_handle, &warnings, &endpoint);
+        }
+
+        let mut state = _state.lock().await;
+        let mut updates = Vec::with_capacity(refreshed.len());
+        let mut stale_caches = Vec::new();
+        for (fetched, changes) in refreshed {
+            let endpoint::FetchedVersion {
+                version,
+                mirrors,
+                original,
+                ..
+            } = fetched;
+            let uuid = version.get_uuid();
+            let old_filename = state.versions.get_file_names().get(&uuid).cloned();
+            if util::import_versions(vec![version.clone()])?.is_empty() {
+                continue;
+            }
+
+            // the stored copy might not have been named after its uuid
+            if old_filename.is_some_and(|f| f != format!("{}.json", uuid))
+                && let Err(e) = util::remove_version(uuid, state.versions.get_file_names())
+            {
+                warn!("Failed to remove old manifest for version {}: {}", uuid, e);
+            }
+
+            let name = util::get_version_name(&version);
+            if util::do_changes_touch_files(&changes) {
+                // what we knew about the caches was checked against the old hashes
+                cache::discard_validation_report(&mut state, uuid, false);
+                cache::discard_validation_report(&mut state, uuid, true);
+                let launcher = &state.config.launcher;
+                if util::get_cache_dir_for_version(&launcher.game_cache_path, &version).exists()
+                    || util::get_cache_dir_for_version(&launcher.offline_cache_path, &version)
+                        .exists()
+                {
+                    stale_caches.push(name.clone());
+                }
+            }
+            // a newer copy doesn't change where the version came from
+            let origin = state.versions.get_origin(uuid);
+            if let Err(e) = state.versions.update_entry(version, origin) {
+                warn!("Failed to update version {}: {}", uuid, e);
+                continue;
+            }
+            state.versions.set_mirrors(uuid, mirrors);
+            if let Err(e) = original.save(uuid) {
+                warn!("Failed to keep original manifest for {}: {}", name, e);
+            }
+            info!("Refreshed version {} ({})", name, changes.join(", "));
+            updates.push(VersionUpdate {
+                uuid,
+                name,
+                changes,
+            });
+        }
+        if !updates.is_empty() {
+            state.save();
+        }
+        drop(state);
+        if !stale_caches.is_empty() {
+            util::send_alert(
+                app_handle.clone(),
+                AlertVariant::Warning,
+                &format!(
+                    "Files changed in {}. Validate or repair their caches before playing.",
+                    stale_caches.join(", ")
+                ),
+            );
+        }
+        Ok(updates)
+    };
+    debug!("refresh_versions {:?}", server_uuid);
+    internal.await
 }
 
 #[tauri::command]
@@ -1237,9 +2021,7 @@ async fn get_servers(app_handle: tauri::AppHandle) -> FlatServers {
     debug!("get_servers");
     let state = app_handle.state::<Mutex<AppState>>();
     let state = state.lock().await;
-    let servers = state.servers.clone();
-    let flat_servers: FlatServers = servers.into();
-    flat_servers
+    state.servers.get_ordered()
 }
 
 #[tauri::command]
@@ -1250,6 +2032,71 @@ async fn get_versions(app_handle: tauri::AppHandle) -> Versions {
     state.versions.clone()
 }
 
+#[derive(Debug, Serialize)]
+struct VersionDetails {
+    uuid: Uuid,
+    name: String,
+    origin: VersionOrigin,
+    /// Nearest first
+    parents: Vec<Uuid>,
+    mirrors: Vec<String>,
+    total_compressed_size: Option<u64>,
+    total_uncompressed_size: Option<u64>,
+    game_cache_size: u64,
+    offline_cache_size: u64,
+    use_count: usize,
+}
+
+#[tauri::command]
+async fn get_version_details(app_handle: tauri::AppHandle) -> CommandResult<Vec<VersionDetails>> {
+    let internal = async {
+        let state = app_handle.state::<Mutex<AppState>>();
+        let state = state.lock().await;
+        let launcher_settings = &state.config.launcher;
+        let mut to_measure = Vec::new();
+        for version in state.versions.get_entries() {
+            let uuid = version.get_uuid();
+            let (total_compressed_size, total_uncompressed_size) =
+                util::get_manifest_sizes(version);
+            let details = VersionDetails {
+                uuid,
+                name: util::get_version_name(version),
+                origin: state.versions.get_origin(uuid),
+                parents: state.versions.get_parent_chain(uuid),
+                mirrors: state.versions.get_mirrors(uuid).to_vec(),
+                total_compressed_size,
+                total_uncompressed_size,
+                game_cache_size: 0,
+                offline_cache_size: 0,
+                use_count: state.get_version_use_count(uuid),
+            };
+            let game_cache_dir =
+                util::get_cache_dir_for_version(&launcher_settings.game_cache_path, version);
+            let offline_cache_dir =
+                util::get_cache_dir_for_version(&launcher_settings.offline_cache_path, version);
+            to_measure.push((details, game_cache_dir, offline_cache_dir));
+        }
+        drop(state);
+
+        // walking the caches can take a while, so keep it off the async runtime
+        let details = tauri::async_runtime::spawn_blocking(move || {
+            to_measure
+                .into_iter()
+                .map(|(mut details, game_cache_dir, offline_cache_dir)| {
+                    details.game_cache_size = util::get_dir_size_or_zero(&game_cache_dir);
+                    details.offline_cache_size = util::get_dir_size_or_zero(&offline_cache_dir);
+                    details
+                })
+                .collect()
+        })
+        .await
+        .map_err(|e| e.to_string())?;
+        Ok(details)
+    };
+    debug!("get_version_details");
+    internal.await
+}
+
 #[tauri::command]
 async fn get_launch_profiles(app_handle: tauri::AppHandle) -> LaunchProfilesView {
     debug!("get_launch_profiles");
@@ -1293,7 +2140,7 @@ async fn update_launch_profile(
         state.save();
         Ok(())
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1325,7 +2172,7 @@ async fn delete_launch_profile(app_handle: tauri::AppHandle, uuid: Uuid) -> Comm
 
         Ok(())
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1333,6 +2180,7 @@ async fn update_config(app_handle: tauri::AppHandle, config: Config) -> CommandR
     debug!("update_config");
     let state = app_handle.state::<Mutex<AppState>>();
     let mut state = state.lock().await;
+    apply_limits(&config.launcher);
     state.config = config;
     state.write_config = true;
     state.save();
@@ -1345,6 +2193,7 @@ async fn reset_launcher_config(app_handle: tauri::AppHandle) -> CommandResult<()
     let default_launcher_config = LauncherSettings::default();
     let state = app_handle.state::<Mutex<AppState>>();
     let mut state = state.lock().await;
+    apply_limits(&default_launcher_config);
     state.config.launcher = default_launcher_config;
     state.write_config = true;
     state.save();
@@ -1388,7 +2237,7 @@ async fn check_for_update() -> CommandResult<Option<UpdateInfo>> {
             url: String::from(DOWNLOAD_PAGE_URL),
         }))
     };
-    internal.await.map_err(|e: Error| e.to_string())
+    internal.await
 }
 
 #[tauri::command]
@@ -1407,6 +2256,15 @@ fn is_debug_mode() -> bool {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
+        // must come first so a second instance hands its deep links over and exits
+        .plugin(tauri_plugin_single_instance::init(|app, _argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .setup(|app| {
@@ -1436,6 +2294,21 @@ pub fn run() {
             // N.B. AppState::load depends on APP_STATICS
             let app_state = AppState::default();
             app.manage(Mutex::new(app_state));
+            status::start_polling(app.handle().clone());
+
+            // openfusion:// links. Installed builds register the scheme at install time,
+            // but AppImages and dev builds have to do it at runtime.
+            #[cfg(any(target_os = "linux", all(debug_assertions, windows)))]
+            if let Err(e) = app.deep_link().register_all() {
+                warn!("Failed to register deep link scheme: {}", e);
+            }
+            let app_handle = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                deep_link::open_urls(&app_handle, event.urls());
+            });
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                deep_link::open_urls(app.handle(), urls);
+            }
 
             Ok(())
         })
@@ -1445,6 +2318,7 @@ pub fn run() {
             is_debug_mode,
             check_for_update,
             get_versions,
+            get_version_details,
             get_servers,
             get_launch_profiles,
             get_config,
@@ -1457,19 +2331,42 @@ pub fn run() {
             add_server,
             update_server,
             delete_server,
+            set_server_favorite,
+            move_server,
+            add_server_folder,
+            rename_server_folder,
+            move_server_folder,
+            delete_server_folder,
+            add_server_directory,
+            remove_server_directory,
+            sync_server_directories,
+            restore_hidden_servers,
             import_version,
             add_version_manual,
+            get_trusted_keys,
+            add_trusted_key,
+            remove_trusted_key,
             remove_version,
             open_folder_for_version,
             live_check,
             get_info_for_server,
             get_announcements_for_server,
             get_versions_for_server,
+            refresh_versions,
             get_player_count_for_server,
+            probe_login_server,
+            get_server_statuses,
+            refresh_server_statuses,
             import_from_openfusionclient,
+            export_servers,
+            import_servers,
             do_register,
             do_login,
             do_logout,
+            get_saved_accounts,
+            select_account,
+            get_token_store_status,
+            unlock_token_store,
             get_session,
             get_account_info,
             send_otp,
@@ -1479,9 +2376,25 @@ pub fn run() {
             do_launch,
             validate_cache,
             download_cache,
+            get_validation_report,
+            cancel_cache_op,
+            export_offline_cache,
+            import_offline_cache,
             delete_cache,
+            scan_caches,
+            collect_caches,
+            get_cache_eviction_plan,
         ])
-        .build(tauri::generate_context![])
+        .build(get_context())
         .unwrap()
         .run(|_, _| ());
 }
+
+/// Handles `openfusionlauncher launch ...` without opening a window. Returns the exit code.
+pub fn run_headless(args: &[String]) -> i32 {
+    cli::run(&get_context(), args)
+}
+
+fn get_context() -> tauri::Context {
+    tauri::generate_context![]
+}
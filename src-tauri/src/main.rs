@@ -3,5 +3,11 @@
 
 fn main() {
     ffbuildtool::set_max_concurrent_downloads(10).unwrap();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "launch") {
+        std::process::exit(scamper::run_headless(&args[1..]));
+    }
+
     scamper::run();
 }
@@ -0,0 +1,78 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::*;
+use serde_json::Value;
+
+// how long a mirror that failed gets skipped for, unless nothing else is left
+const MIRROR_RETRY_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// When each asset host last failed, for the rest of the session
+static MIRROR_FAILURES: LazyLock<Mutex<HashMap<String, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Reads the `mirrors` list from a raw version manifest: base URLs that serve the same
+/// files as its asset URL, in order of preference
+pub(crate) fn parse_manifest_mirrors(manifest_json: &str) -> Vec<String> {
+    let Ok(manifest) = serde_json::from_str::<Value>(manifest_json) else {
+        return Vec::new();
+    };
+    normalize(
+        manifest["mirrors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|m| m.as_str().map(str::to_string))
+            .collect(),
+    )
+}
+
+/// Trims trailing slashes and drops anything that isn't an HTTP(S) URL or is listed twice
+pub(crate) fn normalize(mirrors: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::with_capacity(mirrors.len());
+    for mirror in mirrors {
+        let mirror = mirror.trim().trim_end_matches('/').to_string();
+        if !(mirror.starts_with("http://") || mirror.starts_with("https://")) {
+            warn!("Ignoring mirror {}: not an HTTP URL", mirror);
+            continue;
+        }
+        if !normalized.contains(&mirror) {
+            normalized.push(mirror);
+        }
+    }
+    normalized
+}
+
+/// Puts mirrors that failed recently at the back, keeping the order otherwise
+pub(crate) fn order_by_health(bases: &[String]) -> Vec<String> {
+    let failures = MIRROR_FAILURES.lock().unwrap();
+    let now = Instant::now();
+    let (healthy, failing): (Vec<&String>, Vec<&String>) = bases.iter().partition(|base| {
+        failures
+            .get(*base)
+            .is_none_or(|failed_at| now.duration_since(*failed_at) > MIRROR_RETRY_AFTER)
+    });
+    healthy.into_iter().chain(failing).cloned().collect()
+}
+
+pub(crate) fn mark_failed(base: &str) {
+    warn!("Asset mirror {} is failing; trying the next one", base);
+    MIRROR_FAILURES
+        .lock()
+        .unwrap()
+        .insert(base.to_string(), Instant::now());
+}
+
+pub(crate) fn mark_ok(base: &str) {
+    if MIRROR_FAILURES.lock().unwrap().remove(base).is_some() {
+        info!("Asset mirror {} is back", base);
+    }
+}
+
+/// Whether a response means the host is having trouble, as opposed to the file not being there
+pub(crate) fn is_host_failure(status: reqwest::StatusCode) -> bool {
+    status.is_server_error()
+}
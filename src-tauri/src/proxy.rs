@@ -0,0 +1,562 @@
+use std::{
+    collections::HashMap,
+    io::Write as _,
+    path::{Component, Path, PathBuf},
+    sync::{
+        Arc, Mutex, OnceLock,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use ffbuildtool::Version;
+use log::*;
+use sha2::{Digest as _, Sha256};
+use tokio::{
+    io::{AsyncReadExt as _, AsyncWriteExt as _},
+    net::{TcpListener, TcpStream},
+};
+use uuid::Uuid;
+
+use crate::{
+    Error, Result, cache, mirror,
+    util::{self, ManifestFileInfo},
+};
+
+const MAX_REQUEST_HEAD_SIZE: usize = 16 * 1024;
+const FILE_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Caps how fast downloads come in, shared by everything that goes through an asset proxy
+pub(crate) static DOWNLOAD_THROTTLE: Throttle = Throttle::new();
+
+// no overall timeout, since a throttled bundle can take minutes
+static PROXY_HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+fn get_proxy_http_client() -> &'static reqwest::Client {
+    PROXY_HTTP_CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .user_agent(util::APP_USER_AGENT)
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .unwrap()
+    })
+}
+
+/// Spaces chunks out so their total rate stays under a limit that can be changed at any time
+pub(crate) struct Throttle {
+    // bytes per second; 0 = unlimited
+    rate: AtomicU64,
+    // when the bytes let through so far will have "finished" at the current rate
+    next_free: Mutex<Option<Instant>>,
+}
+impl Throttle {
+    const fn new() -> Self {
+        Self {
+            rate: AtomicU64::new(0),
+            next_free: Mutex::new(None),
+        }
+    }
+
+    pub fn set_rate(&self, bytes_per_sec: Option<u64>) {
+        self.rate
+            .store(bytes_per_sec.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    pub fn is_limited(&self) -> bool {
+        self.rate.load(Ordering::Relaxed) > 0
+    }
+
+    /// Waits until `bytes` more can go through without going over the limit
+    pub async fn consume(&self, bytes: usize) {
+        let rate = self.rate.load(Ordering::Relaxed);
+        if rate == 0 {
+            return;
+        }
+
+        let cost = Duration::from_secs_f64(bytes as f64 / rate as f64);
+        let start = {
+            let mut next_free = self.next_free.lock().unwrap();
+            let now = Instant::now();
+            let start = next_free.filter(|t| *t > now).unwrap_or(now);
+            *next_free = Some(start + cost);
+            start
+        };
+        tokio::time::sleep_until(start.into()).await;
+    }
+}
+
+/// Aborts the proxy task when dropped
+pub(crate) struct ProxyGuard(tokio::task::JoinHandle<()>);
+impl Drop for ProxyGuard {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// An offline cache for the proxy to serve files from and save the files it fetches to
+pub(crate) struct ProxyCache {
+    version_uuid: Uuid,
+    dir: PathBuf,
+    files: HashMap<String, ManifestFileInfo>,
+    // whether each file checked out against the manifest, as of the first time it was served
+    verified: Mutex<HashMap<String, bool>>,
+}
+impl ProxyCache {
+    pub fn new(version: &Version, dir: PathBuf) -> Self {
+        Self {
+            version_uuid: version.get_uuid(),
+            dir,
+            files: util::get_manifest_file_infos(version),
+            verified: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The cached copy of `name`, if there is an intact one. Files the proxy didn't store
+    /// itself get hashed the first time they're asked for, so corrupt ones get fetched again.
+    async fn get_cached(&self, name: &str) -> Option<(PathBuf, u64)> {
+        let info = self.files.get(name)?;
+        let path = self.dir.join(name);
+        let meta = std::fs::metadata(&path).ok()?;
+        if meta.len() != info.size {
+            return None;
+        }
+
+        let known = self.verified.lock().unwrap().get(name).copied();
+        let intact = match known {
+            Some(intact) => intact,
+            None => {
+                let hash_path = path.clone();
+                let hash = tokio::task::spawn_blocking(move || hash_file(&hash_path))
+                    .await
+                    .map_err(|e| Error::from(e.to_string()))
+                    .and_then(|hash| hash);
+                let intact = match hash {
+                    Ok(hash) => hash == info.hash,
+                    Err(e) => {
+                        warn!("Asset proxy couldn't hash cached {}: {}", name, e);
+                        false
+                    }
+                };
+                if !intact {
+                    warn!(
+                        "Cached {} doesn't match the manifest; fetching it again",
+                        name
+                    );
+                }
+                self.verified
+                    .lock()
+                    .unwrap()
+                    .insert(name.to_string(), intact);
+                intact
+            }
+        };
+        intact.then_some((path, info.size))
+    }
+
+    /// Moves a finished download into the cache if it matches the manifest
+    async fn store(&self, name: &str, part_path: &Path, size: u64, hash: &str) -> Result<()> {
+        let info = self.files.get(name).ok_or("Not in manifest")?;
+        check_against_manifest(info, size, hash)?;
+
+        // don't write into the cache while it's being validated, repaired or deleted,
+        // but other downloads can be stored alongside this one
+        let path = self.dir.join(name);
+        let res = cache::unless_op_running(self.version_uuid, true, || {
+            path.parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .and_then(|_| std::fs::rename(part_path, &path))
+        })
+        .await?;
+        res?;
+        self.verified.lock().unwrap().insert(name.to_string(), true);
+        Ok(())
+    }
+}
+
+struct AssetProxy {
+    // the version's own asset URL first, then its mirrors
+    asset_urls: Vec<String>,
+    main_url: Option<String>,
+    cache: Option<ProxyCache>,
+}
+
+/// Starts a local HTTP proxy for `asset_url`, with downloads going through `DOWNLOAD_THROTTLE`.
+/// Requests fall back to `mirrors` when a host can't be reached or errors out.
+/// Requests for the main file go to `main_url` if there is one. With a `cache`, files it has are
+/// served from disk and files it's missing are saved to it once they check out against the manifest.
+/// Returns the URL to use in place of `asset_url` and the task running the proxy.
+pub(crate) async fn start_asset_proxy(
+    asset_url: &str,
+    mirrors: &[String],
+    main_url: Option<&str>,
+    cache: Option<ProxyCache>,
+) -> Result<(String, tokio::task::JoinHandle<()>)> {
+    let proxy = Arc::new(AssetProxy {
+        asset_urls: get_asset_urls(asset_url, mirrors),
+        main_url: main_url.map(str::to_string),
+        cache,
+    });
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let proxy_addr = listener.local_addr()?;
+
+    let handle = tokio::spawn(async move {
+        loop {
+            let (client, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(e) => {
+                    warn!("Asset proxy failed to accept: {}", e);
+                    continue;
+                }
+            };
+            let proxy = proxy.clone();
+            tokio::spawn(async move {
+                if let Err(e) = proxy.handle(client).await {
+                    debug!("Asset proxy connection closed: {}", e);
+                }
+            });
+        }
+    });
+    Ok((format!("http://{}", proxy_addr), handle))
+}
+
+/// Returns a copy of `version` that downloads through an asset proxy if a download speed limit
+/// is set or it has mirrors to fall back on, since ffbuildtool does neither on its own.
+pub(crate) async fn proxy_version(
+    version: &Version,
+    mirrors: &[String],
+) -> Result<(Version, Option<ProxyGuard>)> {
+    if !DOWNLOAD_THROTTLE.is_limited() && mirrors.is_empty() {
+        return Ok((version.clone(), None));
+    }
+
+    let main_url = version.get_main_file_url();
+    let (proxy_url, handle) =
+        start_asset_proxy(&version.get_asset_url(), mirrors, main_url.as_deref(), None).await?;
+    let guard = ProxyGuard(handle);
+    let throttled = util::with_asset_url(version, &proxy_url)?;
+    Ok((throttled, Some(guard)))
+}
+
+/// Downloads the given files of an offline cache again, straight into `dir`, replacing each one
+/// once it checks out against the manifest. Downloads go through `DOWNLOAD_THROTTLE` and fall back
+/// to `mirrors` like the asset proxy does. `on_file` hears whether each file got fixed.
+/// The caller is expected to hold the cache's operation.
+pub(crate) async fn repair_files(
+    version: &Version,
+    mirrors: &[String],
+    dir: &Path,
+    names: &[String],
+    on_file: impl Fn(&str, bool),
+) {
+    let files = util::get_manifest_file_infos(version);
+    let fetcher = AssetProxy {
+        asset_urls: get_asset_urls(&version.get_asset_url(), mirrors),
+        main_url: version.get_main_file_url(),
+        cache: None,
+    };
+    for name in names {
+        let res = match files.get(name) {
+            Some(info) if is_safe_name(name) => fetcher.download_to(name, dir, info).await,
+            _ => Err("Not in manifest".into()),
+        };
+        match &res {
+            Ok(()) => info!("Repaired {}", name),
+            Err(e) => warn!("Failed to repair {}: {}", name, e),
+        }
+        on_file(name, res.is_ok());
+    }
+}
+
+fn get_asset_urls(asset_url: &str, mirrors: &[String]) -> Vec<String> {
+    std::iter::once(asset_url.trim_end_matches('/').to_string())
+        .chain(mirrors.iter().cloned())
+        .collect()
+}
+
+fn check_against_manifest(info: &ManifestFileInfo, size: u64, hash: &str) -> Result<()> {
+    if info.size != size || info.hash != hash {
+        return Err(format!(
+            "Expected {} bytes with hash {}, got {} bytes with hash {}",
+            info.size, info.hash, size, hash
+        )
+        .into());
+    }
+    Ok(())
+}
+
+impl AssetProxy {
+    /// Serves a single request. Every response closes the connection, which keeps this simple
+    /// and costs nothing on loopback.
+    async fn handle(&self, mut client: TcpStream) -> Result<()> {
+        let Some((method, name)) = read_request(&mut client).await? else {
+            return Ok(());
+        };
+        let head_only = match method.as_str() {
+            "GET" => false,
+            "HEAD" => true,
+            _ => return write_status(&mut client, 405, "Method Not Allowed").await,
+        };
+        if !is_safe_name(&name) {
+            return write_status(&mut client, 400, "Bad Request").await;
+        }
+
+        let cached = match &self.cache {
+            Some(cache) => cache.get_cached(&name).await,
+            None => None,
+        };
+        if let Some((path, size)) = cached {
+            debug!("Asset proxy serving {} from cache", name);
+            return serve_file(&mut client, &path, size, head_only).await;
+        }
+
+        let Some(mut response) = self.fetch(&name, head_only).await else {
+            return write_status(&mut client, 502, "Bad Gateway").await;
+        };
+
+        let status = response.status();
+        let mut head = format!(
+            "HTTP/1.1 {} {}\r\nConnection: close\r\n",
+            status.as_u16(),
+            status.canonical_reason().unwrap_or("")
+        );
+        if let Some(content_type) = response.headers().get(reqwest::header::CONTENT_TYPE)
+            && let Ok(content_type) = content_type.to_str()
+        {
+            head.push_str(&format!("Content-Type: {}\r\n", content_type));
+        }
+        if let Some(len) = response.content_length() {
+            head.push_str(&format!("Content-Length: {}\r\n", len));
+        }
+        head.push_str("\r\n");
+        client.write_all(head.as_bytes()).await?;
+        if head_only {
+            return Ok(());
+        }
+
+        // save a copy on the side if it's something the cache should have
+        let mut part = match &self.cache {
+            Some(cache) if status.is_success() && cache.files.contains_key(&name) => {
+                PartFile::create(&cache.dir, &name)
+                    .inspect_err(|e| warn!("Asset proxy can't cache {}: {}", name, e))
+                    .ok()
+            }
+            _ => None,
+        };
+
+        while let Some(chunk) = response.chunk().await? {
+            DOWNLOAD_THROTTLE.consume(chunk.len()).await;
+            client.write_all(&chunk).await?;
+            if let Some(p) = &mut part
+                && let Err(e) = p.write(&chunk)
+            {
+                warn!("Asset proxy stopped caching {}: {}", name, e);
+                part = None;
+            }
+        }
+        client.shutdown().await?;
+
+        if let (Some(mut part), Some(cache)) = (part, &self.cache) {
+            let (size, hash) = part.finish()?;
+            match cache.store(&name, &part.path, size, &hash).await {
+                Ok(()) => info!("Asset proxy cached {}", name),
+                Err(e) => warn!("Asset proxy didn't cache {}: {}", name, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Downloads `name` to `dir`, only replacing what's there if the download is intact
+    async fn download_to(&self, name: &str, dir: &Path, info: &ManifestFileInfo) -> Result<()> {
+        let mut response = self
+            .fetch(name, false)
+            .await
+            .ok_or("No host could serve it")?;
+        if !response.status().is_success() {
+            return Err(format!("Got {}", response.status()).into());
+        }
+
+        let mut part = PartFile::create(dir, name)?;
+        while let Some(chunk) = response.chunk().await? {
+            DOWNLOAD_THROTTLE.consume(chunk.len()).await;
+            part.write(&chunk)?;
+        }
+        let (size, hash) = part.finish()?;
+        check_against_manifest(info, size, &hash)?;
+        std::fs::rename(&part.path, dir.join(name))?;
+        Ok(())
+    }
+
+    /// Requests `name` from each host in turn, healthiest first, until one answers without
+    /// a server error. None if none of them do.
+    async fn fetch(&self, name: &str, head_only: bool) -> Option<reqwest::Response> {
+        let primary = &self.asset_urls[0];
+        for base in mirror::order_by_health(&self.asset_urls) {
+            let url = match &self.main_url {
+                Some(main_url) if name == util::MAIN_FILE_NAME && &base == primary => {
+                    main_url.clone()
+                }
+                _ => format!("{}/{}", base, name),
+            };
+            let request = if head_only {
+                get_proxy_http_client().head(&url)
+            } else {
+                get_proxy_http_client().get(&url)
+            };
+            match request.send().await {
+                Ok(response) if !mirror::is_host_failure(response.status()) => {
+                    mirror::mark_ok(&base);
+                    return Some(response);
+                }
+                Ok(response) => warn!("Asset proxy got {} for {}", response.status(), url),
+                Err(e) => warn!("Asset proxy failed to fetch {}: {}", url, e),
+            }
+            mirror::mark_failed(&base);
+        }
+        None
+    }
+}
+
+/// SHA-256 of a file on disk, in the same form as the manifest lists it
+fn hash_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; FILE_BUFFER_SIZE];
+    loop {
+        let n = std::io::Read::read(&mut file, &mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// A download in progress, hashed as it's written
+struct PartFile {
+    path: PathBuf,
+    file: Option<std::fs::File>,
+    hasher: Sha256,
+    size: u64,
+}
+impl PartFile {
+    fn create(dir: &Path, name: &str) -> Result<Self> {
+        // unique, so two requests for the same file don't write over each other
+        let path = dir.join(format!("{}.{}.part", name, Uuid::new_v4()));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = std::fs::File::create(&path)?;
+        Ok(Self {
+            path,
+            file: Some(file),
+            hasher: Sha256::new(),
+            size: 0,
+        })
+    }
+
+    fn write(&mut self, data: &[u8]) -> Result<()> {
+        let file = self.file.as_mut().ok_or("Part file already finished")?;
+        file.write_all(data)?;
+        self.hasher.update(data);
+        self.size += data.len() as u64;
+        Ok(())
+    }
+
+    /// Closes the file so it can be moved, returning its size and hash
+    fn finish(&mut self) -> Result<(u64, String)> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+        }
+        Ok((self.size, format!("{:x}", self.hasher.finalize_reset())))
+    }
+}
+impl Drop for PartFile {
+    fn drop(&mut self) {
+        // abandoned downloads shouldn't leave anything behind. stored ones have been moved by now.
+        self.file.take();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Reads the request line and headers, returning the method and the requested file
+/// relative to the asset URL. None if the client hung up first.
+async fn read_request(client: &mut TcpStream) -> Result<Option<(String, String)>> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 1024];
+    while !buf.windows(4).any(|w| w == b"\r\n\r\n") {
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(None);
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if buf.len() > MAX_REQUEST_HEAD_SIZE {
+            return Err("Request head too large".into());
+        }
+    }
+
+    let head = String::from_utf8_lossy(&buf);
+    let mut parts = head.lines().next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err("Malformed request line".into());
+    };
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    let name = percent_decode(path.trim_start_matches('/'));
+    Ok(Some((method.to_string(), name)))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = s.get(i + 1..i + 3)
+            && let Ok(b) = u8::from_str_radix(hex, 16)
+        {
+            out.push(b);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Whether a requested name stays inside the cache directory
+fn is_safe_name(name: &str) -> bool {
+    !name.is_empty()
+        && Path::new(name)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)))
+}
+
+async fn write_status(client: &mut TcpStream, code: u16, reason: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nConnection: close\r\nContent-Length: 0\r\n\r\n",
+        code, reason
+    );
+    client.write_all(response.as_bytes()).await?;
+    client.shutdown().await?;
+    Ok(())
+}
+
+async fn serve_file(client: &mut TcpStream, path: &Path, size: u64, head_only: bool) -> Result<()> {
+    let head = format!(
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Type: application/octet-stream\r\nContent-Length: {}\r\n\r\n",
+        size
+    );
+    client.write_all(head.as_bytes()).await?;
+    if !head_only {
+        let mut file = tokio::fs::File::open(path).await?;
+        let mut buf = vec![0; FILE_BUFFER_SIZE];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            client.write_all(&buf[..n]).await?;
+        }
+    }
+    client.shutdown().await?;
+    Ok(())
+}
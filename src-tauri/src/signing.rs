@@ -0,0 +1,292 @@
+use std::path::{Path, PathBuf};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use ed25519_dalek::{Signature, VerifyingKey};
+use ffbuildtool::Version;
+use log::*;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{Error, Result, state::get_app_statics, util};
+
+/// Detached signatures are published next to the file they sign
+const SIGNATURE_SUFFIX: &str = ".sig";
+
+pub(crate) fn get_signature_url(url: &str) -> String {
+    format!("{}{}", url, SIGNATURE_SUFFIX)
+}
+
+/// Parses a base64-encoded ed25519 public key
+pub(crate) fn parse_public_key(public_key: &str) -> Result<VerifyingKey> {
+    let bytes = STANDARD
+        .decode(public_key.trim())
+        .map_err(|e| format!("Invalid public key: {}", e))?;
+    let bytes: [u8; 32] = bytes
+        .try_into()
+        .map_err(|_| "Invalid public key: expected 32 bytes")?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("Invalid public key: {}", e).into())
+}
+
+/// Checks a base64-encoded detached ed25519 signature over `data`.
+/// `what` describes the signed file for the error message.
+pub(crate) fn verify_detached(
+    data: &[u8],
+    signature: &str,
+    public_key: &VerifyingKey,
+    what: &str,
+) -> Result<()> {
+    let invalid = || Error::InvalidSignature(what.to_string());
+    let bytes = STANDARD.decode(signature.trim()).map_err(|_| invalid())?;
+    let signature = Signature::from_slice(&bytes).map_err(|_| invalid())?;
+    public_key
+        .verify_strict(data, &signature)
+        .map_err(|_| invalid())
+}
+
+const TRUSTED_KEYS_FILE_NAME: &str = "trusted_keys.json";
+
+/// What to do with version manifests that aren't signed by a trusted key
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SignaturePolicy {
+    /// Don't check signatures at all
+    Off,
+    /// Accept the manifest, but warn the user
+    #[default]
+    Warn,
+    /// Refuse the manifest
+    Require,
+}
+
+/// Publisher key manifests can be signed with
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TrustedKey {
+    name: String,
+    public_key: String,
+    /// Shipped with the launcher, as opposed to added by the user
+    #[serde(skip_deserializing)]
+    builtin: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct TrustedKeysFile {
+    keys: Vec<TrustedKey>,
+}
+
+/// Trusted publisher keys. The builtin ones come from `defaults/trusted_keys.json`,
+/// and the user can add their own, which are kept in the app data dir.
+#[derive(Debug, Clone, Default)]
+pub struct TrustStore {
+    keys: Vec<TrustedKey>,
+}
+impl TrustStore {
+    pub fn new() -> Self {
+        let builtin_path = get_app_statics()
+            .resource_dir
+            .join("defaults")
+            .join(TRUSTED_KEYS_FILE_NAME);
+        let user_path = get_app_statics().app_data_dir.join(TRUSTED_KEYS_FILE_NAME);
+
+        let mut keys = Vec::new();
+        for (path, builtin) in [(builtin_path, true), (user_path, false)] {
+            match Self::load_file(&path) {
+                Ok(file) => keys.extend(file.keys.into_iter().map(|mut key| {
+                    key.builtin = builtin;
+                    key
+                })),
+                Err(e) => warn!("Failed to load trusted keys from {:?}: {}", path, e),
+            }
+        }
+        Self { keys }
+    }
+
+    fn load_file(path: &Path) -> Result<TrustedKeysFile> {
+        if !path.exists() {
+            return Ok(TrustedKeysFile::default());
+        }
+        let file_str = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&file_str)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let file = TrustedKeysFile {
+            keys: self.keys.iter().filter(|k| !k.builtin).cloned().collect(),
+        };
+        let path = get_app_statics().app_data_dir.join(TRUSTED_KEYS_FILE_NAME);
+        let file_str = serde_json::to_string_pretty(&file)?;
+        std::fs::write(path, file_str)?;
+        Ok(())
+    }
+
+    pub fn get_keys(&self) -> &[TrustedKey] {
+        &self.keys
+    }
+
+    pub fn add_key(&mut self, name: &str, public_key: &str) -> Result<()> {
+        let public_key = public_key.trim();
+        parse_public_key(public_key)?;
+        if self.keys.iter().any(|k| k.public_key == public_key) {
+            return Err("Key is already trusted".into());
+        }
+        self.keys.push(TrustedKey {
+            name: name.to_string(),
+            public_key: public_key.to_string(),
+            builtin: false,
+        });
+        Ok(())
+    }
+
+    pub fn remove_key(&mut self, public_key: &str) -> Result<()> {
+        match self.keys.iter().position(|k| k.public_key == public_key) {
+            Some(idx) if self.keys[idx].builtin => Err("Builtin keys can't be removed".into()),
+            Some(idx) => {
+                self.keys.remove(idx);
+                Ok(())
+            }
+            None => Err("Key not found".into()),
+        }
+    }
+
+    /// Returns the name of the trusted key that signed `data`, if any
+    fn find_signer(&self, data: &[u8], signature: &str, what: &str) -> Option<&str> {
+        self.keys
+            .iter()
+            .find(|key| {
+                parse_public_key(&key.public_key)
+                    .and_then(|pk| verify_detached(data, signature, &pk, what))
+                    .is_ok()
+            })
+            .map(|key| key.name.as_str())
+    }
+}
+
+/// Snapshot of the trust store and policy, so manifests can be checked without holding the app state
+#[derive(Debug, Clone, Default)]
+pub struct ManifestVerifier {
+    store: TrustStore,
+    policy: SignaturePolicy,
+}
+impl ManifestVerifier {
+    pub fn new(store: TrustStore, policy: SignaturePolicy) -> Self {
+        Self { store, policy }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.policy != SignaturePolicy::Off
+    }
+
+    /// Checks a manifest against its detached signature, if there is one.
+    /// Returns a warning for the user if the manifest should be accepted anyway,
+    /// or an error if the policy says to refuse it.
+    pub fn check(
+        &self,
+        data: &[u8],
+        signature: Option<&str>,
+        what: &str,
+    ) -> Result<Option<String>> {
+        if !self.is_enabled() {
+            return Ok(None);
+        }
+
+        let problem = match signature {
+            Some(signature) => match self.store.find_signer(data, signature, what) {
+                Some(signer) => {
+                    debug!("Manifest {} is signed by {}", what, signer);
+                    return Ok(None);
+                }
+                None => Error::InvalidSignature(what.to_string()),
+            },
+            None => Error::UnsignedManifest(what.to_string()),
+        };
+
+        match self.policy {
+            SignaturePolicy::Require => Err(problem),
+            _ => {
+                warn!("{}", problem);
+                Ok(Some(problem.to_string()))
+            }
+        }
+    }
+}
+
+/// Fetches the detached signature for a remote file. Missing signatures aren't an error.
+pub(crate) async fn fetch_signature(url: &str) -> Option<String> {
+    util::do_simple_get(&get_signature_url(url)).await.ok()
+}
+
+/// Reads the detached signature for a local file, if there is one
+pub(crate) fn read_signature(path: &str) -> Option<String> {
+    std::fs::read_to_string(get_signature_url(path)).ok()
+}
+
+const MANIFESTS_DIR_NAME: &str = "manifests";
+
+/// A manifest exactly as it was published, with its detached signature if it had one.
+/// The copies under `versions` are re-serialized and no longer match their signatures,
+/// so these are what get handed on in server bundles and offline cache archives.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SignedManifest {
+    pub manifest: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+impl SignedManifest {
+    pub fn new(manifest: String, signature: Option<String>) -> Self {
+        Self {
+            manifest,
+            signature,
+        }
+    }
+
+    fn get_path(uuid: Uuid) -> PathBuf {
+        get_app_statics()
+            .app_data_dir
+            .join(MANIFESTS_DIR_NAME)
+            .join(format!("{}.json", uuid))
+    }
+
+    /// Returns the original manifest for a version, or an unsigned copy of it
+    /// if the original wasn't kept (builtin versions and ones added by older launchers)
+    pub fn load(version: &Version) -> Result<Self> {
+        let path = Self::get_path(version.get_uuid());
+        if let Ok(manifest) = std::fs::read_to_string(&path) {
+            return Ok(Self::new(manifest, read_signature(&path.to_string_lossy())));
+        }
+        Ok(Self::new(serde_json::to_string_pretty(version)?, None))
+    }
+
+    pub fn save(&self, uuid: Uuid) -> Result<()> {
+        let path = Self::get_path(uuid);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&path, &self.manifest)?;
+        let signature_path = get_signature_url(&path.to_string_lossy());
+        match &self.signature {
+            Some(signature) => std::fs::write(signature_path, signature)?,
+            None => {
+                // don't leave a signature for an older copy behind
+                let _ = std::fs::remove_file(signature_path);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remove(uuid: Uuid) {
+        let path = Self::get_path(uuid);
+        let _ = std::fs::remove_file(get_signature_url(&path.to_string_lossy()));
+        if let Err(e) = std::fs::remove_file(&path)
+            && e.kind() != std::io::ErrorKind::NotFound
+        {
+            warn!("Failed to remove original manifest for {}: {}", uuid, e);
+        }
+    }
+
+    pub fn check(&self, verifier: &ManifestVerifier, what: &str) -> Result<Option<String>> {
+        verifier.check(self.manifest.as_bytes(), self.signature.as_deref(), what)
+    }
+
+    pub fn parse(&self) -> Result<Version> {
+        Ok(serde_json::from_str(&self.manifest)?)
+    }
+}
@@ -1,19 +1,31 @@
-use std::{collections::HashMap, path::PathBuf, process::Command, sync::OnceLock};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+    process::Command,
+    sync::OnceLock,
+};
 
 use ffbuildtool::Version;
 use log::*;
 use serde::{Deserialize, Serialize};
-use tauri::{Manager, path::BaseDirectory};
+use tauri::Manager;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
 use crate::{
-    NewServerDetails, Result,
+    Error, NewServerDetails, Result,
+    cache::{CacheUsage, ValidationReports},
     config::{GameSettings, LauncherSettings},
+    mirror,
+    signing::{ManifestVerifier, SignedManifest, TrustStore},
+    token_store::{TokenStore, TokenStoreStatus},
     util::{self, AlertVariant},
 };
 
 const OPENFUSIONCLIENT_PATH: &str = "OpenFusionClient";
+const PLAINTEXT_TOKENS_FILE_NAME: &str = "tokens.json";
+const VERSION_ORIGINS_FILE_NAME: &str = "version_origins.json";
+const VERSION_MIRRORS_FILE_NAME: &str = "version_mirrors.json";
 
 static APP_STATICS: OnceLock<AppStatics> = OnceLock::new();
 
@@ -23,6 +35,11 @@ pub fn init_app_statics(app: &mut tauri::App) {
     APP_STATICS.set(statics).unwrap();
 }
 
+pub fn init_app_statics_headless(context: &tauri::Context) {
+    let statics = AppStatics::load_headless(context);
+    APP_STATICS.set(statics).unwrap();
+}
+
 pub fn get_app_statics() -> &'static AppStatics {
     APP_STATICS.get().unwrap()
 }
@@ -42,23 +59,39 @@ impl AppStatics {
         let version = app.handle().package_info().version.to_string();
         let path_resolver = app.handle().path();
         let app_data_dir = path_resolver.app_data_dir().unwrap();
+        let resource_dir = path_resolver.resource_dir().unwrap();
+        let app_cache_dir = path_resolver.app_cache_dir().unwrap();
+        Self::from_dirs(version, app_data_dir, resource_dir, app_cache_dir)
+    }
 
-        let mut resource_dir = path_resolver.resource_dir().unwrap();
+    /// Resolves the same directories as `load` without a running Tauri app.
+    /// Mirrors what Tauri's desktop path resolver does under the hood.
+    fn load_headless(context: &tauri::Context) -> Self {
+        let package_info = context.package_info();
+        let identifier = &context.config().identifier;
+        let version = package_info.version.to_string();
+        let app_data_dir = dirs::data_dir().unwrap().join(identifier);
+        let resource_dir =
+            tauri::utils::platform::resource_dir(package_info, &tauri::Env::default()).unwrap();
+        let app_cache_dir = dirs::cache_dir().unwrap().join(identifier);
+        Self::from_dirs(version, app_data_dir, resource_dir, app_cache_dir)
+    }
+
+    fn from_dirs(
+        version: String,
+        app_data_dir: PathBuf,
+        mut resource_dir: PathBuf,
+        app_cache_dir: PathBuf,
+    ) -> Self {
         if !std::fs::exists(resource_dir.join("ffrunner.exe")).unwrap_or(false) {
             // Resource directory is incorrect. Assume standalone build
             // and use the current executable's directory as the resource dir.
             resource_dir = std::env::current_exe().unwrap().parent().unwrap().into();
         }
 
-        let ff_cache_dir = path_resolver
-            .resolve("ffcache", BaseDirectory::AppCache)
-            .unwrap();
-        let offline_cache_dir = path_resolver
-            .resolve("offline_cache", BaseDirectory::AppCache)
-            .unwrap();
-        let compat_data_dir = path_resolver
-            .resolve("compat_data", BaseDirectory::AppCache)
-            .unwrap();
+        let ff_cache_dir = app_cache_dir.join("ffcache");
+        let offline_cache_dir = app_cache_dir.join("offline_cache");
+        let compat_data_dir = app_cache_dir.join("compat_data");
         let ffrunner_log_path = app_data_dir.join("ffrunner.log");
 
         Self {
@@ -84,14 +117,17 @@ pub struct AppState {
     pub versions: Versions,
     pub servers: Servers,
     pub tokens: Tokens,
+    pub trust_store: TrustStore,
+    pub cache_usage: CacheUsage,
+    pub validation_reports: ValidationReports,
     //
-    pub temp_tokens: HashMap<Uuid, String>,
+    pub temp_tokens: HashMap<Uuid, RefreshToken>,
     pub write_config: bool,
     pub launch_cmd: Option<Command>,
     pub proxy: Option<JoinHandle<()>>,
 }
 impl AppState {
-    pub fn load(app_handle: tauri::AppHandle) -> Self {
+    pub fn load(alerts: &util::AlertSink) -> Self {
         let config = Config::new();
         let (mut config, write_config) = match config {
             Ok(config) => (config, true),
@@ -101,16 +137,27 @@ impl AppState {
                     // config exists but is malformed. warn and do not overwrite
                     let msg = format!("Failed to load config: {}", e);
                     warn!("{}", msg);
-                    util::send_alert(app_handle, AlertVariant::Warning, &msg);
+                    alerts.send(AlertVariant::Warning, &msg);
                 }
                 (Config::load_default(), !config_exists)
             }
         };
 
+        crate::apply_limits(&config.launcher);
+
         let versions = Versions::new();
         let launch_profiles = LaunchProfiles::new(&mut config);
         let mut servers = Servers::new();
         let tokens = Tokens::new();
+        let trust_store = TrustStore::new();
+        let cache_usage = CacheUsage::load();
+        let validation_reports = ValidationReports::load();
+        if tokens.is_locked() && tokens.store.has_saved_data() {
+            alerts.send(
+                AlertVariant::Info,
+                "Saved logins are locked. Unlock them in Settings > Authentication.",
+            );
+        }
 
         // Old server entries use the version description instead of the UUID.
         // This migrates them to use the UUID instead.
@@ -123,6 +170,9 @@ impl AppState {
             versions,
             servers,
             tokens,
+            trust_store,
+            cache_usage,
+            validation_reports,
             //
             temp_tokens: HashMap::new(),
             write_config,
@@ -158,7 +208,15 @@ impl AppState {
         if let Err(e) = self.servers.save() {
             warn!("Failed to save servers: {}", e);
         }
-        if let Err(e) = self.tokens.save() {
+        if let Err(e) = self.versions.save() {
+            warn!("Failed to save version origins: {}", e);
+        }
+        if let Err(e) = self.trust_store.save() {
+            warn!("Failed to save trusted keys: {}", e);
+        }
+        if self.tokens.is_locked() {
+            debug!("Token store is locked; not saving tokens");
+        } else if let Err(e) = self.tokens.save() {
             warn!("Failed to save tokens: {}", e);
         }
     }
@@ -184,15 +242,126 @@ impl AppState {
     }
 
     pub fn import_versions(&mut self) -> Result<usize> {
-        let versions = &mut self.versions.versions;
         let legacy_versions = Versions::load_from_openfusionclient()?;
-        let to_import = Versions::calculate_merge(versions, legacy_versions);
+        let to_import = Versions::calculate_merge(&self.versions.versions, legacy_versions);
         let imported = util::import_versions(to_import)?;
         let num_imported = imported.len();
-        versions.extend(imported);
+        for version in imported {
+            self.versions.add_entry(version, VersionOrigin::Legacy);
+        }
         Ok(num_imported)
     }
 
+    /// Bundles up the given servers for sharing,
+    /// optionally with the manifests of the versions they use
+    pub fn export_server_bundle(
+        &self,
+        uuids: &[Uuid],
+        include_versions: bool,
+    ) -> Result<ServerBundle> {
+        let mut servers = Vec::with_capacity(uuids.len());
+        for uuid in uuids {
+            let server = self
+                .servers
+                .get_entry(*uuid)
+                .ok_or(Error::ServerNotFound(*uuid))?;
+            servers.push(FlatServer::from(server.clone()));
+        }
+
+        let mut versions: Vec<BundledVersion> = Vec::new();
+        if include_versions {
+            // for endpoint servers, the preferred version is the best we can do
+            let version_uuids = servers
+                .iter()
+                .filter_map(|s| s.version.as_deref())
+                .filter_map(|v| Uuid::parse_str(v).ok());
+            let mut seen = HashSet::new();
+            for version_uuid in version_uuids {
+                if !seen.insert(version_uuid) {
+                    continue;
+                }
+                if let Some(version) = self.versions.get_entry(version_uuid) {
+                    // the original, so the other side can check its signature
+                    versions.push(BundledVersion::Signed(SignedManifest::load(version)?));
+                }
+            }
+        }
+
+        Ok(ServerBundle { servers, versions })
+    }
+
+    /// Adds the servers and versions from a shared bundle that we don't already have.
+    /// New manifests are checked against the trusted keys the same way imported ones are.
+    /// Returns the number of versions and servers imported, plus any signature warnings.
+    pub fn import_server_bundle(
+        &mut self,
+        bundle: ServerBundle,
+    ) -> Result<(usize, usize, Vec<String>)> {
+        let mut originals = HashMap::new();
+        let mut bundled = Vec::with_capacity(bundle.versions.len());
+        for entry in bundle.versions {
+            let original = match entry {
+                BundledVersion::Signed(original) => original,
+                // bundles from older launchers only have the re-serialized manifest
+                BundledVersion::Plain(version) => {
+                    SignedManifest::new(serde_json::to_string_pretty(&version)?, None)
+                }
+            };
+            let version = original.parse()?;
+            originals.insert(version.get_uuid(), original);
+            bundled.push(version);
+        }
+
+        let to_import = Versions::calculate_merge(&self.versions.versions, bundled);
+        let verifier = self.get_manifest_verifier();
+        let mut warnings = Vec::new();
+        for version in &to_import {
+            let what = format!(
+                "Manifest for {} in server bundle",
+                util::get_version_name(version)
+            );
+            warnings.extend(originals[&version.get_uuid()].check(&verifier, &what)?);
+        }
+
+        let imported = util::import_versions(to_import)?;
+        let num_versions = imported.len();
+        for version in imported {
+            let uuid = version.get_uuid();
+            let original = &originals[&uuid];
+            if let Err(e) = original.save(uuid) {
+                warn!("Failed to keep original manifest for {}: {}", uuid, e);
+            }
+            self.versions
+                .set_mirrors(uuid, mirror::parse_manifest_mirrors(&original.manifest));
+            self.versions.add_entry(version, VersionOrigin::AppData);
+        }
+
+        let (valid, invalid): (Vec<FlatServer>, Vec<FlatServer>) =
+            bundle.servers.into_iter().partition(FlatServer::is_valid);
+        if !invalid.is_empty() {
+            warn!("Skipping {} malformed server entries", invalid.len());
+        }
+        let mut servers = Servers {
+            servers: valid.into_iter().map(Server::from).collect(),
+            ..Default::default()
+        };
+        // folders and directory subscriptions are local to each launcher
+        for server in &mut servers.servers {
+            server.folder = None;
+            server.managed_by = None;
+        }
+        Self::fixup_server_versions(&mut servers, &self.versions);
+        let num_servers = self.servers.merge(&servers);
+        Ok((num_versions, num_servers, warnings))
+    }
+
+    pub fn get_manifest_verifier(&self) -> ManifestVerifier {
+        ManifestVerifier::new(
+            self.trust_store.clone(),
+            self.config.launcher.manifest_signatures,
+        )
+    }
+
     pub fn get_version_use_count(&self, uuid: Uuid) -> usize {
         self.servers
             .servers
@@ -271,40 +440,140 @@ impl From<LegacyVersion> for Version {
     }
 }
 
+/// Where a version's manifest came from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum VersionOrigin {
+    /// Shipped with the launcher
+    Builtin,
+    /// Imported or added by the user
+    #[default]
+    AppData,
+    /// Fetched from an endpoint server
+    Endpoint,
+    /// Imported from OpenFusionClient
+    Legacy,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Versions {
     versions: Vec<Version>,
     filenames: HashMap<Uuid, String>,
+    #[serde(skip)]
+    origins: HashMap<Uuid, VersionOrigin>,
+    #[serde(skip)]
+    mirrors: HashMap<Uuid, Vec<String>>,
 }
 impl Versions {
     fn new() -> Self {
         let mut versions = Vec::new();
         let mut filenames = HashMap::new();
+        let mut origins = HashMap::new();
+        let mut mirrors = HashMap::new();
 
-        match Self::load_builtins(&mut filenames) {
+        match Self::load_builtins(&mut filenames, &mut mirrors) {
             Ok(builtins) => {
                 info!("Loaded {} built-in versions", builtins.len());
+                origins.extend(
+                    builtins
+                        .iter()
+                        .map(|v| (v.get_uuid(), VersionOrigin::Builtin)),
+                );
                 versions.extend(builtins);
             }
             Err(e) => warn!("Failed to load built-in versions: {}", e),
         }
 
-        match Self::load_appdata(&mut filenames) {
+        match Self::load_appdata(&mut filenames, &mut mirrors) {
             Ok(loaded) => {
                 let to_merge = Self::calculate_merge(&versions, loaded);
                 info!("Loaded {} versions from app data", to_merge.len());
+                let stored_origins = Self::load_origins().unwrap_or_else(|e| {
+                    warn!("Failed to load version origins: {}", e);
+                    HashMap::new()
+                });
+                for version in &to_merge {
+                    let uuid = version.get_uuid();
+                    let origin = stored_origins.get(&uuid).copied().unwrap_or_default();
+                    origins.insert(uuid, origin);
+                }
                 versions.extend(to_merge);
             }
             Err(e) => warn!("Failed to load versions: {}", e),
         };
 
+        // exported manifests don't keep their mirrors, so the ones we've been given are kept on the side
+        match Self::load_mirrors() {
+            Ok(stored_mirrors) => mirrors.extend(stored_mirrors),
+            Err(e) => warn!("Failed to load version mirrors: {}", e),
+        }
+
         Self {
             versions,
             filenames,
+            origins,
+            mirrors,
+        }
+    }
+
+    fn load_mirrors() -> Result<HashMap<Uuid, Vec<String>>> {
+        let mirrors_path = get_app_statics()
+            .app_data_dir
+            .join(VERSION_MIRRORS_FILE_NAME);
+        if !mirrors_path.exists() {
+            return Ok(HashMap::new());
+        }
+        let mirrors_str = std::fs::read_to_string(mirrors_path)?;
+        Ok(serde_json::from_str(&mirrors_str)?)
+    }
+
+    fn load_origins() -> Result<HashMap<Uuid, VersionOrigin>> {
+        let origins_path = get_app_statics()
+            .app_data_dir
+            .join(VERSION_ORIGINS_FILE_NAME);
+        if !origins_path.exists() {
+            return Ok(HashMap::new());
         }
+        let origins_str = std::fs::read_to_string(origins_path)?;
+        Ok(serde_json::from_str(&origins_str)?)
+    }
+
+    /// The manifests themselves are written by `util::import_versions`;
+    /// this only keeps track of where the app data ones came from and their mirrors
+    fn save(&self) -> Result<()> {
+        let origins: HashMap<Uuid, VersionOrigin> = self
+            .origins
+            .iter()
+            .filter(|(uuid, origin)| {
+                !matches!(origin, VersionOrigin::Builtin | VersionOrigin::AppData)
+                    && self.get_entry(**uuid).is_some()
+            })
+            .map(|(uuid, origin)| (*uuid, *origin))
+            .collect();
+        let origins_path = get_app_statics()
+            .app_data_dir
+            .join(VERSION_ORIGINS_FILE_NAME);
+        let origins_str = serde_json::to_string_pretty(&origins)?;
+        std::fs::write(origins_path, origins_str)?;
+
+        let mirrors: HashMap<&Uuid, &Vec<String>> = self
+            .mirrors
+            .iter()
+            .filter(|(uuid, _)| self.get_entry(**uuid).is_some())
+            .collect();
+        let mirrors_path = get_app_statics()
+            .app_data_dir
+            .join(VERSION_MIRRORS_FILE_NAME);
+        let mirrors_str = serde_json::to_string_pretty(&mirrors)?;
+        std::fs::write(mirrors_path, mirrors_str)?;
+        Ok(())
     }
 
-    fn load_internal(path: &str, filenames: &mut HashMap<Uuid, String>) -> Result<Vec<Version>> {
+    fn load_internal(
+        path: &str,
+        filenames: &mut HashMap<Uuid, String>,
+        mirrors: &mut HashMap<Uuid, Vec<String>>,
+    ) -> Result<Vec<Version>> {
         if !std::fs::exists(path)? {
             return Ok(Vec::new());
         }
@@ -319,6 +588,12 @@ impl Versions {
                     Ok(version) => {
                         let filename = path.file_name().unwrap().to_string_lossy().to_string();
                         filenames.insert(version.get_uuid(), filename);
+                        let manifest_mirrors = std::fs::read_to_string(&path)
+                            .map(|json| mirror::parse_manifest_mirrors(&json))
+                            .unwrap_or_default();
+                        if !manifest_mirrors.is_empty() {
+                            mirrors.insert(version.get_uuid(), manifest_mirrors);
+                        }
                         versions.push(version);
                     }
                     Err(e) => {
@@ -333,14 +608,20 @@ impl Versions {
         Ok(versions)
     }
 
-    fn load_appdata(filenames: &mut HashMap<Uuid, String>) -> Result<Vec<Version>> {
+    fn load_appdata(
+        filenames: &mut HashMap<Uuid, String>,
+        mirrors: &mut HashMap<Uuid, Vec<String>>,
+    ) -> Result<Vec<Version>> {
         let versions_path = get_app_statics().app_data_dir.join("versions");
-        Self::load_internal(&versions_path.to_string_lossy(), filenames)
+        Self::load_internal(&versions_path.to_string_lossy(), filenames, mirrors)
     }
 
-    fn load_builtins(filenames: &mut HashMap<Uuid, String>) -> Result<Vec<Version>> {
+    fn load_builtins(
+        filenames: &mut HashMap<Uuid, String>,
+        mirrors: &mut HashMap<Uuid, Vec<String>>,
+    ) -> Result<Vec<Version>> {
         let builtins_path = get_app_statics().resource_dir.join("defaults/versions");
-        Self::load_internal(&builtins_path.to_string_lossy(), filenames)
+        Self::load_internal(&builtins_path.to_string_lossy(), filenames, mirrors)
     }
 
     fn load_from_openfusionclient() -> Result<Vec<Version>> {
@@ -376,12 +657,75 @@ impl Versions {
         to_merge
     }
 
-    pub fn add_entry(&mut self, version: Version) {
+    pub fn add_entry(&mut self, version: Version, origin: VersionOrigin) {
+        self.origins.insert(version.get_uuid(), origin);
         self.versions.push(version);
     }
 
     pub fn remove_entry(&mut self, uuid: Uuid) {
         self.versions.retain(|v| v.get_uuid() != uuid);
+        self.origins.remove(&uuid);
+        self.mirrors.remove(&uuid);
+    }
+
+    /// Swaps in a newer copy of a version's manifest.
+    /// The caller writes it to app data with `util::import_versions`, which names it `<uuid>.json`.
+    pub fn update_entry(&mut self, version: Version, origin: VersionOrigin) -> Result<()> {
+        let uuid = version.get_uuid();
+        let existing = self
+            .versions
+            .iter_mut()
+            .find(|v| v.get_uuid() == uuid)
+            .ok_or(Error::VersionNotFound(uuid))?;
+        *existing = version;
+        self.filenames.insert(uuid, format!("{}.json", uuid));
+        self.origins.insert(uuid, origin);
+        Ok(())
+    }
+
+    pub fn get_entries(&self) -> &[Version] {
+        &self.versions
+    }
+
+    /// Other base URLs serving the same files as the version's asset URL, in order of preference
+    pub fn get_mirrors(&self, uuid: Uuid) -> &[String] {
+        self.mirrors
+            .get(&uuid)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn set_mirrors(&mut self, uuid: Uuid, mirrors: Vec<String>) {
+        if mirrors.is_empty() {
+            self.mirrors.remove(&uuid);
+        } else {
+            self.mirrors.insert(uuid, mirrors);
+        }
+    }
+
+    pub fn get_origin(&self, uuid: Uuid) -> VersionOrigin {
+        self.origins.get(&uuid).copied().unwrap_or_default()
+    }
+
+    /// Shipped with the launcher, so never rewritten
+    pub fn is_builtin(&self, uuid: Uuid) -> bool {
+        self.get_origin(uuid) == VersionOrigin::Builtin
+    }
+
+    /// Follows a version's parents, nearest first.
+    /// A parent we don't have ends the chain, but is still included.
+    pub fn get_parent_chain(&self, uuid: Uuid) -> Vec<Uuid> {
+        let mut chain = Vec::new();
+        let mut current = self.get_entry(uuid).and_then(|v| v.get_parent_uuid());
+        while let Some(parent) = current {
+            // guard against malformed manifests that loop back on themselves
+            if parent == uuid || chain.contains(&parent) {
+                break;
+            }
+            chain.push(parent);
+            current = self.get_entry(parent).and_then(|v| v.get_parent_uuid());
+        }
+        chain
     }
 
     pub fn get_entry(&self, uuid: Uuid) -> Option<&Version> {
@@ -397,7 +741,7 @@ impl Versions {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum ServerInfo {
     Simple {
         ip: String,
@@ -409,6 +753,23 @@ pub enum ServerInfo {
     },
 }
 
+/// Shareable collection of server entries, optionally carrying
+/// the manifests of the versions they use
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ServerBundle {
+    servers: Vec<FlatServer>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    versions: Vec<BundledVersion>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum BundledVersion {
+    Signed(SignedManifest),
+    /// Older launchers put the manifest in as-is, without its signature
+    Plain(Version),
+}
+
 /// We store servers in a "flat" format for ease of serialization on disk
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FlatServer {
@@ -417,6 +778,11 @@ pub struct FlatServer {
     ip: Option<String>,
     version: Option<String>,
     endpoint: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    folder: Option<Uuid>,
+    /// The directory subscription this entry comes from, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    managed_by: Option<Uuid>,
 }
 impl From<Server> for FlatServer {
     fn from(server: Server) -> Self {
@@ -428,6 +794,8 @@ impl From<Server> for FlatServer {
                 ip: Some(ip),
                 version: Some(version),
                 endpoint: None,
+                folder: server.folder,
+                managed_by: server.managed_by,
             },
             ServerInfo::Endpoint {
                 endpoint,
@@ -438,21 +806,73 @@ impl From<Server> for FlatServer {
                 ip: None,
                 version: preferred_version,
                 endpoint: Some(endpoint),
+                folder: server.folder,
+                managed_by: server.managed_by,
             },
         }
     }
 }
 
+impl FlatServer {
+    /// Whether this entry has enough information to be turned into a `Server`
+    fn is_valid(&self) -> bool {
+        self.endpoint.is_some() || (self.ip.is_some() && self.version.is_some())
+    }
+}
+
+/// User-defined group of servers in the server list
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerFolder {
+    uuid: Uuid,
+    name: String,
+}
+
+/// Subscription to a remote, signed list of servers
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ServerDirectory {
+    uuid: Uuid,
+    url: String,
+    /// Base64 ed25519 key the list's detached signature is checked against
+    public_key: String,
+    #[serde(default)]
+    last_synced: Option<u64>,
+    #[serde(default)]
+    last_error: Option<String>,
+}
+impl ServerDirectory {
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+
+    pub fn get_url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn get_public_key(&self) -> &str {
+        &self.public_key
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FlatServers {
     servers: Vec<FlatServer>,
     favorites: Vec<Uuid>,
+    #[serde(default)]
+    folders: Vec<ServerFolder>,
+    #[serde(default)]
+    directories: Vec<ServerDirectory>,
+    /// Directory entries the user removed, so they don't come back on the next sync
+    #[serde(default)]
+    hidden: Vec<Uuid>,
 }
 impl From<Servers> for FlatServers {
     fn from(servers: Servers) -> Self {
         Self {
             servers: servers.servers.into_iter().map(FlatServer::from).collect(),
             favorites: servers.favorites,
+            folders: servers.folders,
+            directories: servers.directories,
+            hidden: servers.hidden,
         }
     }
 }
@@ -462,6 +882,8 @@ pub struct Server {
     uuid: Uuid,
     description: Option<String>,
     pub info: ServerInfo,
+    folder: Option<Uuid>,
+    managed_by: Option<Uuid>,
 }
 impl From<FlatServer> for Server {
     fn from(flat: FlatServer) -> Self {
@@ -480,10 +902,16 @@ impl From<FlatServer> for Server {
             uuid: flat.uuid,
             description: flat.description,
             info,
+            folder: flat.folder,
+            managed_by: flat.managed_by,
         }
     }
 }
 impl Server {
+    pub fn get_uuid(&self) -> Uuid {
+        self.uuid
+    }
+
     pub fn get_description(&self) -> Option<String> {
         self.description.clone()
     }
@@ -491,8 +919,12 @@ impl Server {
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Servers {
+    /// In the user's order
     servers: Vec<Server>,
     favorites: Vec<Uuid>,
+    folders: Vec<ServerFolder>,
+    directories: Vec<ServerDirectory>,
+    hidden: Vec<Uuid>,
 }
 impl Servers {
     fn new() -> Self {
@@ -510,7 +942,8 @@ impl Servers {
     fn load_internal(path: &str) -> Result<Self> {
         let servers_str = std::fs::read_to_string(path)?;
         let flat_servers: FlatServers = serde_json::from_str(&servers_str)?;
-        let servers: Self = flat_servers.into();
+        let mut servers: Self = flat_servers.into();
+        servers.prune();
         Ok(servers)
     }
 
@@ -561,12 +994,183 @@ impl Servers {
         }
     }
 
+    pub fn get_entries(&self) -> &[Server] {
+        &self.servers
+    }
+
+    /// Servers in the order the list shows them: ungrouped servers first, then each folder
+    /// in turn, with favorites at the top of each group. Otherwise the user's order is kept.
+    pub fn get_ordered(&self) -> FlatServers {
+        let folder_pos = |server: &Server| {
+            server
+                .folder
+                .and_then(|f| self.folders.iter().position(|folder| folder.uuid == f))
+        };
+        let mut servers = self.servers.clone();
+        // stable sort, so the user's order is kept within each group
+        servers.sort_by_key(|s| (folder_pos(s), !self.is_favorite(s.uuid)));
+        FlatServers {
+            servers: servers.into_iter().map(FlatServer::from).collect(),
+            favorites: self.favorites.clone(),
+            folders: self.folders.clone(),
+            directories: self.directories.clone(),
+            hidden: self.hidden.clone(),
+        }
+    }
+
+    pub fn is_favorite(&self, uuid: Uuid) -> bool {
+        self.favorites.contains(&uuid)
+    }
+
+    pub fn set_favorite(&mut self, uuid: Uuid, favorite: bool) -> Result<()> {
+        if self.get_entry(uuid).is_none() {
+            return Err(Error::ServerNotFound(uuid));
+        }
+        self.favorites.retain(|f| *f != uuid);
+        if favorite {
+            self.favorites.push(uuid);
+        }
+        Ok(())
+    }
+
+    /// Moves a server into `folder` (or out of any folder if None),
+    /// placing it right before `before`, or at the end if that's None
+    pub fn move_entry(
+        &mut self,
+        uuid: Uuid,
+        folder: Option<Uuid>,
+        before: Option<Uuid>,
+    ) -> Result<()> {
+        if let Some(folder) = folder
+            && !self.folders.iter().any(|f| f.uuid == folder)
+        {
+            return Err(Error::FolderNotFound(folder));
+        }
+        let idx = self
+            .servers
+            .iter()
+            .position(|s| s.uuid == uuid)
+            .ok_or(Error::ServerNotFound(uuid))?;
+        let mut server = self.servers.remove(idx);
+        server.folder = folder;
+
+        let new_idx = match before {
+            Some(before) if before != uuid => {
+                match self.servers.iter().position(|s| s.uuid == before) {
+                    Some(before_idx) => before_idx,
+                    None => {
+                        self.servers.insert(idx, server);
+                        return Err(Error::ServerNotFound(before));
+                    }
+                }
+            }
+            Some(_) => idx,
+            None => self.servers.len(),
+        };
+        self.servers.insert(new_idx, server);
+        Ok(())
+    }
+
+    pub fn add_folder(&mut self, name: &str) -> Uuid {
+        let uuid = Uuid::new_v4();
+        self.folders.push(ServerFolder {
+            uuid,
+            name: name.to_string(),
+        });
+        uuid
+    }
+
+    pub fn rename_folder(&mut self, uuid: Uuid, name: &str) -> Result<()> {
+        let folder = self
+            .folders
+            .iter_mut()
+            .find(|f| f.uuid == uuid)
+            .ok_or(Error::FolderNotFound(uuid))?;
+        folder.name = name.to_string();
+        Ok(())
+    }
+
+    /// Removes a folder. The servers in it are kept and become ungrouped.
+    pub fn remove_folder(&mut self, uuid: Uuid) {
+        self.folders.retain(|f| f.uuid != uuid);
+        for server in &mut self.servers {
+            if server.folder == Some(uuid) {
+                server.folder = None;
+            }
+        }
+    }
+
+    /// Moves a folder right before `before`, or to the end if that's None
+    pub fn move_folder(&mut self, uuid: Uuid, before: Option<Uuid>) -> Result<()> {
+        let idx = self
+            .folders
+            .iter()
+            .position(|f| f.uuid == uuid)
+            .ok_or(Error::FolderNotFound(uuid))?;
+        let new_idx = match before {
+            Some(before) => self
+                .folders
+                .iter()
+                .filter(|f| f.uuid != uuid)
+                .position(|f| f.uuid == before)
+                .ok_or(Error::FolderNotFound(before))?,
+            None => self.folders.len() - 1,
+        };
+        let folder = self.folders.remove(idx);
+        self.folders.insert(new_idx, folder);
+        Ok(())
+    }
+
+    /// Drops favorites, folder assignments and directory links that point at things that no longer exist
+    fn prune(&mut self) {
+        let mut seen = HashSet::new();
+        let servers = &self.servers;
+        self.favorites
+            .retain(|f| servers.iter().any(|s| s.uuid == *f) && seen.insert(*f));
+        for server in &mut self.servers {
+            if let Some(folder) = server.folder
+                && !self.folders.iter().any(|f| f.uuid == folder)
+            {
+                server.folder = None;
+            }
+            if let Some(directory) = server.managed_by
+                && !self.directories.iter().any(|d| d.uuid == directory)
+            {
+                server.managed_by = None;
+            }
+        }
+    }
+
     pub fn get_entry(&self, uuid: Uuid) -> Option<&Server> {
         self.servers.iter().find(|s| s.uuid == uuid)
     }
 
+    pub fn get_entry_by_endpoint(&self, endpoint: &str) -> Option<&Server> {
+        self.servers.iter().find(|s| match &s.info {
+            ServerInfo::Endpoint { endpoint: e, .. } => e.eq_ignore_ascii_case(endpoint),
+            ServerInfo::Simple { .. } => false,
+        })
+    }
+
+    /// Looks a server up by UUID, falling back to an exact description match
+    pub fn find_entry(&self, uuid_or_name: &str) -> Option<&Server> {
+        if let Ok(uuid) = Uuid::parse_str(uuid_or_name) {
+            return self.get_entry(uuid);
+        }
+        self.servers
+            .iter()
+            .find(|s| s.description.as_deref() == Some(uuid_or_name))
+    }
+
+    /// Removes a server. Directory entries are hidden instead so the next sync doesn't re-add them.
     pub fn remove_entry(&mut self, uuid: Uuid) {
+        if self.get_entry(uuid).is_some_and(|s| s.managed_by.is_some())
+            && !self.hidden.contains(&uuid)
+        {
+            self.hidden.push(uuid);
+        }
         self.servers.retain(|s| s.uuid != uuid);
+        self.favorites.retain(|f| *f != uuid);
     }
 
     pub fn add_entry(&mut self, details: NewServerDetails) -> Uuid {
@@ -587,18 +1191,120 @@ impl Servers {
             uuid,
             description: Some(description),
             info,
+            folder: None,
+            managed_by: None,
+        });
+        uuid
+    }
+
+    pub fn get_directories(&self) -> &[ServerDirectory] {
+        &self.directories
+    }
+
+    pub fn add_directory(&mut self, url: &str, public_key: &str) -> Uuid {
+        let uuid = Uuid::new_v4();
+        self.directories.push(ServerDirectory {
+            uuid,
+            url: url.to_string(),
+            public_key: public_key.to_string(),
+            last_synced: None,
+            last_error: None,
         });
         uuid
     }
 
-    pub fn update_entry(&mut self, entry: Server) -> Result<()> {
+    /// Unsubscribes from a directory, removing the servers it added
+    pub fn remove_directory(&mut self, uuid: Uuid) {
+        self.directories.retain(|d| d.uuid != uuid);
+        let removed: Vec<Uuid> = self
+            .servers
+            .iter()
+            .filter(|s| s.managed_by == Some(uuid))
+            .map(|s| s.uuid)
+            .collect();
+        self.servers.retain(|s| s.managed_by != Some(uuid));
+        self.favorites.retain(|f| !removed.contains(f));
+        self.prune();
+    }
+
+    /// Brings back all directory entries the user removed, starting with the next sync
+    pub fn clear_hidden(&mut self) {
+        self.hidden.clear();
+    }
+
+    pub fn set_directory_error(&mut self, uuid: Uuid, error: &str) {
+        if let Some(directory) = self.directories.iter_mut().find(|d| d.uuid == uuid) {
+            directory.last_error = Some(error.to_string());
+        }
+    }
+
+    /// Brings the entries managed by a directory in line with its latest list.
+    /// New entries are added, existing ones updated in place, and ones that are gone removed.
+    /// Entries the user already has on their own or has hidden are left alone.
+    /// Returns whether anything changed.
+    pub fn apply_directory(&mut self, uuid: Uuid, entries: Vec<FlatServer>) -> Result<bool> {
+        let Some(directory) = self.directories.iter_mut().find(|d| d.uuid == uuid) else {
+            return Err(format!("Server directory {} not found", uuid).into());
+        };
+        directory.last_synced = Some(util::get_timestamp());
+        directory.last_error = None;
+
+        let (entries, invalid): (Vec<FlatServer>, Vec<FlatServer>) =
+            entries.into_iter().partition(FlatServer::is_valid);
+        if !invalid.is_empty() {
+            warn!(
+                "Skipping {} malformed entries from server directory {}",
+                invalid.len(),
+                uuid
+            );
+        }
+
+        let mut changed = false;
+        let before = self.servers.len();
+        self.servers
+            .retain(|s| s.managed_by != Some(uuid) || entries.iter().any(|e| e.uuid == s.uuid));
+        changed |= self.servers.len() != before;
+
+        for entry in entries {
+            if self.hidden.contains(&entry.uuid) {
+                continue;
+            }
+            let mut server = Server::from(entry);
+            server.managed_by = Some(uuid);
+            match self.servers.iter_mut().find(|s| s.uuid == server.uuid) {
+                Some(existing) if existing.managed_by == Some(uuid) => {
+                    if existing.description != server.description || existing.info != server.info {
+                        existing.description = server.description;
+                        existing.info = server.info;
+                        changed = true;
+                    }
+                }
+                Some(_) => {}
+                None => {
+                    server.folder = None;
+                    self.servers.push(server);
+                    changed = true;
+                }
+            }
+        }
+
+        let servers = &self.servers;
+        self.favorites
+            .retain(|f| servers.iter().any(|s| s.uuid == *f));
+        Ok(changed)
+    }
+
+    /// Updates a server's details. Its folder and directory are managed separately and are left as is.
+    pub fn update_entry(&mut self, mut entry: Server) -> Result<()> {
         for server in &mut self.servers {
             if server.uuid == entry.uuid {
+                entry.folder = server.folder;
+                entry.managed_by = server.managed_by;
                 *server = entry;
                 return Ok(());
             }
         }
-        Err(format!("Server with UUID {} not found", entry.uuid).into())
+        Err(Error::ServerNotFound(entry.uuid))
     }
 }
 
@@ -607,6 +1313,9 @@ impl From<FlatServers> for Servers {
         Self {
             servers: flat.servers.into_iter().map(Server::from).collect(),
             favorites: flat.favorites,
+            folders: flat.folders,
+            directories: flat.directories,
+            hidden: flat.hidden,
         }
     }
 }
@@ -769,43 +1478,394 @@ impl From<&LaunchProfiles> for LaunchProfilesView {
     }
 }
 
-/// Refresh tokens for each server
+/// Seconds before actual expiry at which a refresh token is considered stale,
+/// so we don't hand out a token that dies halfway through a launch
+const TOKEN_EXPIRY_LEEWAY_SECS: u64 = 60;
+
+/// A saved refresh token, along with its lifetime if we know it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RefreshToken {
+    token: String,
+    issued_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<u64>,
+}
+impl RefreshToken {
+    /// Builds a token record. If the server didn't tell us when the token expires,
+    /// we try to read the issue and expiry times out of the token itself (if it's a JWT).
+    pub fn new(token: &str, expires_at: Option<u64>) -> Self {
+        let claims = util::decode_jwt_claims(token);
+        let issued_at = claims
+            .as_ref()
+            .and_then(|c| c.iat)
+            .unwrap_or_else(util::get_timestamp);
+        let expires_at = expires_at.or(claims.and_then(|c| c.exp));
+        Self {
+            token: token.to_string(),
+            issued_at,
+            expires_at,
+        }
+    }
+
+    pub fn get_token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn is_expired(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => util::get_timestamp() + TOKEN_EXPIRY_LEEWAY_SECS >= expires_at,
+            None => false,
+        }
+    }
+}
+
+/// Tokens used to be stored as bare strings
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredToken {
+    Legacy(String),
+    Current(RefreshToken),
+}
+impl From<StoredToken> for RefreshToken {
+    fn from(stored: StoredToken) -> Self {
+        match stored {
+            StoredToken::Legacy(token) => RefreshToken::new(&token, None),
+            StoredToken::Current(token) => token,
+        }
+    }
+}
+
+/// Servers used to only remember a single token
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAccounts {
+    Current(ServerAccounts),
+    Single(StoredToken),
+}
+impl From<StoredAccounts> for ServerAccounts {
+    fn from(stored: StoredAccounts) -> Self {
+        match stored {
+            StoredAccounts::Current(accounts) => accounts,
+            StoredAccounts::Single(token) => Self {
+                // we don't know who this token belongs to until we get a session with it
+                accounts: vec![SavedAccount {
+                    username: String::new(),
+                    token: token.into(),
+                }],
+                selected: None,
+            },
+        }
+    }
+}
+
+fn deserialize_tokens<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<HashMap<Uuid, ServerAccounts>, D::Error> {
+    let stored: HashMap<Uuid, StoredAccounts> = HashMap::deserialize(deserializer)?;
+    Ok(stored.into_iter().map(|(k, v)| (k, v.into())).collect())
+}
+
+/// A saved login for one account on a server
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SavedAccount {
+    username: String,
+    #[serde(flatten)]
+    token: RefreshToken,
+}
+impl SavedAccount {
+    pub fn get_username(&self) -> &str {
+        &self.username
+    }
+
+    pub fn get_refresh_token(&self) -> &RefreshToken {
+        &self.token
+    }
+}
+
+/// Saved logins for a single server, along with the one to use by default
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct ServerAccounts {
+    accounts: Vec<SavedAccount>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    selected: Option<String>,
+}
+impl ServerAccounts {
+    fn find(&self, username: &str) -> Option<&SavedAccount> {
+        self.accounts.iter().find(|a| a.username == username)
+    }
+
+    /// Looks up an account by username, or the selected account if no username is given
+    fn get(&self, username: Option<&str>) -> Option<&SavedAccount> {
+        match username {
+            Some(username) => self.find(username),
+            None => self
+                .selected
+                .as_deref()
+                .and_then(|selected| self.find(selected))
+                .or(self.accounts.first()),
+        }
+    }
+
+    fn insert(&mut self, account: SavedAccount) {
+        match self
+            .accounts
+            .iter_mut()
+            .find(|a| a.username == account.username)
+        {
+            Some(existing) => *existing = account,
+            None => self.accounts.push(account),
+        }
+    }
+
+    fn remove(&mut self, username: &str) {
+        self.accounts.retain(|a| a.username != username);
+        if self.selected.as_deref() == Some(username) {
+            self.selected = None;
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+}
+
+/// Frontend view of the saved accounts for a server, without the tokens
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SavedAccountsView {
+    usernames: Vec<String>,
+    selected: Option<String>,
+}
+impl From<&ServerAccounts> for SavedAccountsView {
+    fn from(accounts: &ServerAccounts) -> Self {
+        Self {
+            usernames: accounts
+                .accounts
+                .iter()
+                .map(|a| a.username.clone())
+                .collect(),
+            selected: accounts.get(None).map(|a| a.username.clone()),
+        }
+    }
+}
+
+/// Saved accounts for each server.
+/// Persisted encrypted through `TokenStore`; older versions kept them in plaintext.
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Tokens {
-    tokens: HashMap<Uuid, String>,
+    #[serde(deserialize_with = "deserialize_tokens")]
+    tokens: HashMap<Uuid, ServerAccounts>,
+    #[serde(skip)]
+    store: TokenStore,
 }
 impl Tokens {
     fn new() -> Self {
-        Self::load().unwrap_or_default()
+        let store = TokenStore::open();
+        let mut tokens = match Self::load(&store) {
+            Ok(tokens) => tokens,
+            Err(Error::TokenStoreLocked) => {
+                info!("Token store is locked, saved logins unavailable until unlocked");
+                Self::default()
+            }
+            Err(e) => {
+                warn!("Failed to load tokens: {}", e);
+                Self::default()
+            }
+        };
+        tokens.store = store;
+
+        // Tokens used to be saved in plaintext. Pull them in here;
+        // the plaintext file is deleted once they've been saved to the encrypted store.
+        let mut migrated = false;
+        match Self::load_plaintext() {
+            Ok(Some(plaintext)) => {
+                info!(
+                    "Migrating plaintext refresh tokens for {} servers",
+                    plaintext.tokens.len()
+                );
+                tokens.merge(plaintext.tokens);
+                migrated = true;
+            }
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load plaintext tokens: {}", e),
+        }
+
+        let pruned = tokens.prune_expired();
+        if pruned > 0 {
+            info!("Cleared {} expired refresh tokens", pruned);
+        }
+
+        // a locked store can't be written yet, so the plaintext file stays until it's unlocked
+        if migrated && let Err(e) = tokens.save() {
+            warn!("Failed to save migrated tokens: {}", e);
+        }
+        tokens
     }
 
-    fn load() -> Result<Self> {
-        let tokens_path = get_app_statics().app_data_dir.join("tokens.json");
+    fn load(store: &TokenStore) -> Result<Self> {
+        let Some(tokens_bytes) = store.read()? else {
+            return Ok(Self::default());
+        };
+        let tokens: Self = serde_json::from_slice(&tokens_bytes)?;
+        Ok(tokens)
+    }
+
+    fn load_plaintext() -> Result<Option<Self>> {
+        let tokens_path = get_app_statics()
+            .app_data_dir
+            .join(PLAINTEXT_TOKENS_FILE_NAME);
+        if !tokens_path.exists() {
+            return Ok(None);
+        }
         let tokens_str = std::fs::read_to_string(tokens_path)?;
         let tokens: Self = serde_json::from_str(&tokens_str)?;
-        Ok(tokens)
+        Ok(Some(tokens))
     }
 
     fn save(&self) -> Result<()> {
-        let tokens_path = get_app_statics().app_data_dir.join("tokens.json");
-        let tokens_str = serde_json::to_string_pretty(self)?;
-        std::fs::write(tokens_path, tokens_str)?;
+        let tokens_bytes = serde_json::to_vec(self)?;
+        self.store.write(&tokens_bytes)?;
+
+        // anything in the plaintext file made it into the encrypted store
+        let tokens_path = get_app_statics()
+            .app_data_dir
+            .join(PLAINTEXT_TOKENS_FILE_NAME);
+        if tokens_path.exists() {
+            match std::fs::remove_file(tokens_path) {
+                Ok(()) => info!("Removed plaintext tokens file"),
+                Err(e) => warn!("Failed to remove plaintext tokens file: {}", e),
+            }
+        }
         Ok(())
     }
 
-    pub fn save_token(&mut self, server_uuid: Uuid, token: &str) {
-        self.tokens.insert(server_uuid, token.to_string());
+    /// Adds accounts we don't already have. Existing accounts win since they're newer.
+    fn merge(&mut self, other: HashMap<Uuid, ServerAccounts>) {
+        for (server_uuid, other_accounts) in other {
+            let accounts = self.tokens.entry(server_uuid).or_default();
+            for account in other_accounts.accounts {
+                if accounts.find(&account.username).is_none() {
+                    accounts.accounts.push(account);
+                }
+            }
+            if accounts.selected.is_none() {
+                accounts.selected = other_accounts.selected;
+            }
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.store.is_locked()
+    }
+
+    pub fn get_store_status(&self) -> TokenStoreStatus {
+        self.store.get_status()
     }
 
-    pub fn get_token(&self, server_uuid: Uuid) -> Option<String> {
-        self.tokens.get(&server_uuid).cloned()
+    /// Unlocks a passphrase-protected store, folding the tokens on disk
+    /// in with any saved this session
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        self.store.unlock(passphrase)?;
+        let stored = Self::load(&self.store)?;
+        self.merge(stored.tokens);
+        self.prune_expired();
+        self.save()
     }
 
-    pub fn remove_token(&mut self, server_uuid: Uuid) {
-        self.tokens.remove(&server_uuid);
+    /// Saves (or replaces) the token for an account. Doesn't change which account is selected.
+    pub fn save_token(&mut self, server_uuid: Uuid, username: &str, token: RefreshToken) {
+        self.tokens
+            .entry(server_uuid)
+            .or_default()
+            .insert(SavedAccount {
+                username: username.to_string(),
+                token,
+            });
+    }
+
+    /// Gets the saved account with the given username, or the selected account if no username is given
+    pub fn get_token(&self, server_uuid: Uuid, username: Option<&str>) -> Option<SavedAccount> {
+        self.tokens
+            .get(&server_uuid)
+            .and_then(|accounts| accounts.get(username))
+            .cloned()
+    }
+
+    pub fn get_accounts(&self, server_uuid: Uuid) -> SavedAccountsView {
+        self.tokens
+            .get(&server_uuid)
+            .map(SavedAccountsView::from)
+            .unwrap_or_default()
+    }
+
+    pub fn select_account(&mut self, server_uuid: Uuid, username: &str) -> Result<()> {
+        let accounts = self
+            .tokens
+            .get_mut(&server_uuid)
+            .filter(|accounts| accounts.find(username).is_some())
+            .ok_or(Error::NotLoggedIn)?;
+        accounts.selected = Some(username.to_string());
+        Ok(())
+    }
+
+    /// Updates the username of a saved account, keeping its token and selection
+    pub fn rename_account(&mut self, server_uuid: Uuid, old_username: &str, new_username: &str) {
+        let Some(accounts) = self.tokens.get_mut(&server_uuid) else {
+            return;
+        };
+        let Some(account) = accounts.find(old_username).cloned() else {
+            return;
+        };
+        let was_selected = accounts.selected.as_deref() == Some(old_username);
+        accounts.remove(old_username);
+        // if the account was also saved under its real name, that login is newer
+        if accounts.find(new_username).is_none() {
+            accounts.insert(SavedAccount {
+                username: new_username.to_string(),
+                token: account.token,
+            });
+        }
+        if was_selected {
+            accounts.selected = Some(new_username.to_string());
+        }
+    }
+
+    /// Removes a single account, or every account for the server if no username is given
+    pub fn remove_token(&mut self, server_uuid: Uuid, username: Option<&str>) {
+        match username {
+            Some(username) => {
+                if let Some(accounts) = self.tokens.get_mut(&server_uuid) {
+                    accounts.remove(username);
+                    if accounts.is_empty() {
+                        self.tokens.remove(&server_uuid);
+                    }
+                }
+            }
+            None => {
+                self.tokens.remove(&server_uuid);
+            }
+        }
     }
 
     pub fn clear(&mut self) {
         self.tokens.clear();
     }
+
+    /// Drops any tokens that are known to have expired. Returns how many were removed.
+    pub fn prune_expired(&mut self) -> usize {
+        let mut pruned = 0;
+        for accounts in self.tokens.values_mut() {
+            let expired: Vec<String> = accounts
+                .accounts
+                .iter()
+                .filter(|a| a.token.is_expired())
+                .map(|a| a.username.clone())
+                .collect();
+            for username in &expired {
+                accounts.remove(username);
+            }
+            pruned += expired.len();
+        }
+        self.tokens.retain(|_, accounts| !accounts.is_empty());
+        pruned
+    }
 }
@@ -0,0 +1,204 @@
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::*;
+use serde::Serialize;
+use tauri::{Emitter as _, Manager as _};
+use uuid::Uuid;
+
+use crate::{
+    config::AddressFamily,
+    endpoint,
+    state::{AppState, ServerInfo},
+    util,
+};
+
+const SERVER_STATUS_EVENT: &str = "server_status";
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Last known status of each server, so the frontend can catch up without waiting for a poll
+static STATUS_CACHE: LazyLock<Mutex<HashMap<Uuid, ServerStatus>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Game versions each endpoint server advertised the last time its info could be fetched.
+/// Kept while a server is unreachable, since it'll likely want the same versions when it's back.
+static SUPPORTED_VERSIONS_CACHE: LazyLock<Mutex<HashMap<Uuid, Vec<String>>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Held for the duration of a poll
+static POLL_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ServerStatus {
+    uuid: Uuid,
+    online: bool,
+    player_count: Option<usize>,
+    latency_ms: Option<u64>,
+    api_version: Option<String>,
+}
+impl ServerStatus {
+    fn offline(uuid: Uuid) -> Self {
+        Self {
+            uuid,
+            online: false,
+            player_count: None,
+            latency_ms: None,
+            api_version: None,
+        }
+    }
+
+    /// Latency changes on every poll, so it's left out when deciding whether to notify
+    fn differs_from(&self, other: &Self) -> bool {
+        self.online != other.online
+            || self.player_count != other.player_count
+            || self.api_version != other.api_version
+    }
+}
+
+/// Spawns the background task that keeps the status cache fresh
+pub(crate) fn start_polling(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            poll_all(&app_handle).await;
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// Polls once in the background, e.g. so a newly added server doesn't wait for the next cycle
+pub(crate) fn request_poll(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        poll_all(&app_handle).await;
+    });
+}
+
+pub(crate) fn get_cached_statuses() -> HashMap<Uuid, ServerStatus> {
+    STATUS_CACHE.lock().unwrap().clone()
+}
+
+/// Versions advertised by the endpoint servers whose info has been fetched so far
+pub(crate) fn get_cached_supported_versions() -> HashMap<Uuid, Vec<String>> {
+    SUPPORTED_VERSIONS_CACHE.lock().unwrap().clone()
+}
+
+/// Probes every server once, updating the cache and emitting
+/// a `server_status` event for each server whose status changed
+pub(crate) async fn poll_all(app_handle: &tauri::AppHandle) {
+    // one poll at a time, so a slow one can't overwrite the results of a newer one
+    let _polling = POLL_LOCK.lock().await;
+
+    let state = app_handle.state::<tokio::sync::Mutex<AppState>>();
+    let state = state.lock().await;
+    let address_family = state.config.launcher.address_family;
+    let servers: Vec<(Uuid, ServerInfo)> = state
+        .servers
+        .get_entries()
+        .iter()
+        .map(|s| (s.get_uuid(), s.info.clone()))
+        .collect();
+    drop(state);
+
+    let probes: Vec<_> = servers
+        .into_iter()
+        .map(|(uuid, info)| tauri::async_runtime::spawn(probe_server(uuid, info, address_family)))
+        .collect();
+
+    let mut statuses = HashMap::with_capacity(probes.len());
+    for probe in probes {
+        match probe.await {
+            Ok(status) => {
+                statuses.insert(status.uuid, status);
+            }
+            Err(e) => warn!("Server status probe failed: {}", e),
+        }
+    }
+
+    let changed: Vec<ServerStatus> = {
+        let mut cache = STATUS_CACHE.lock().unwrap();
+        let changed = statuses
+            .values()
+            .filter(|status| {
+                cache
+                    .get(&status.uuid)
+                    .is_none_or(|cached| status.differs_from(cached))
+            })
+            .cloned()
+            .collect();
+        // replacing the whole cache also drops servers that were removed
+        *cache = statuses;
+        SUPPORTED_VERSIONS_CACHE
+            .lock()
+            .unwrap()
+            .retain(|uuid, _| cache.contains_key(uuid));
+        changed
+    };
+
+    for status in changed {
+        debug!(
+            "Server {} is now {}",
+            status.uuid,
+            if status.online { "online" } else { "offline" }
+        );
+        if let Err(e) = app_handle.emit(SERVER_STATUS_EVENT, status) {
+            error!("Failed to emit server status event: {}", e);
+        }
+    }
+}
+
+async fn probe_server(uuid: Uuid, info: ServerInfo, address_family: AddressFamily) -> ServerStatus {
+    match info {
+        ServerInfo::Simple { ip, .. } => probe_simple(uuid, &ip, address_family).await,
+        ServerInfo::Endpoint { endpoint, .. } => probe_endpoint(uuid, &endpoint).await,
+    }
+}
+
+/// Simple servers don't have an API, so all we can tell is whether the port accepts connections
+async fn probe_simple(uuid: Uuid, addr: &str, address_family: AddressFamily) -> ServerStatus {
+    let Ok(addr) = util::resolve_server_addr(addr, address_family).await else {
+        return ServerStatus::offline(uuid);
+    };
+    match util::probe_game_addr(&addr).await {
+        Ok(latency) => ServerStatus {
+            uuid,
+            online: true,
+            player_count: None,
+            latency_ms: Some(latency.as_millis() as u64),
+            api_version: None,
+        },
+        Err(_) => ServerStatus::offline(uuid),
+    }
+}
+
+async fn probe_endpoint(uuid: Uuid, endpoint_host: &str) -> ServerStatus {
+    let start = Instant::now();
+    let status =
+        match tokio::time::timeout(PROBE_TIMEOUT, endpoint::get_status(endpoint_host)).await {
+            Ok(Ok(status)) => status,
+            _ => return ServerStatus::offline(uuid),
+        };
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    let api_version =
+        match tokio::time::timeout(PROBE_TIMEOUT, endpoint::get_info(endpoint_host)).await {
+            Ok(Ok(info)) => {
+                SUPPORTED_VERSIONS_CACHE
+                    .lock()
+                    .unwrap()
+                    .insert(uuid, info.get_supported_versions());
+                Some(info.api_version)
+            }
+            _ => None,
+        };
+
+    ServerStatus {
+        uuid,
+        online: true,
+        player_count: Some(status.player_count),
+        latency_ms: Some(latency_ms),
+        api_version,
+    }
+}
@@ -0,0 +1,256 @@
+use std::{fmt, path::PathBuf};
+
+use argon2::Argon2;
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use chacha20poly1305::{
+    AeadCore as _, ChaCha20Poly1305, Key, KeyInit as _, Nonce,
+    aead::{Aead as _, OsRng, rand_core::RngCore as _},
+};
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result, state::get_app_statics};
+
+const KEYRING_SERVICE: &str = "OpenFusionLauncher";
+const KEYRING_USER: &str = "token-store-key";
+const STORE_FILE_NAME: &str = "tokens.enc";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEYRING_FILE_UNREADABLE: &str = "Saved logins are protected by the OS keyring, which isn't available right now. \
+    Restore access to the keyring, or delete tokens.enc from the app data folder to start over with a passphrase.";
+
+/// Where the key protecting the token file comes from
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySource {
+    /// Random key kept in the platform secret service
+    Keyring,
+    /// Key derived from a passphrase the user enters each session
+    Passphrase,
+}
+
+/// On-disk layout of `tokens.enc`
+#[derive(Serialize, Deserialize)]
+struct StoreFile {
+    key_source: KeySource,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    salt: Option<String>,
+    nonce: String,
+    ciphertext: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TokenStoreStatus {
+    key_source: KeySource,
+    locked: bool,
+}
+
+/// Encrypted storage for saved refresh tokens.
+/// The key lives in the OS keyring when there is one. Otherwise (e.g. Linux without
+/// a Secret Service provider) it is derived from a user passphrase, and the store
+/// stays locked until `unlock` is called.
+pub struct TokenStore {
+    key_source: KeySource,
+    key: Option<Key>,
+    salt: Option<Vec<u8>>,
+}
+impl Default for TokenStore {
+    fn default() -> Self {
+        Self {
+            key_source: KeySource::Passphrase,
+            key: None,
+            salt: None,
+        }
+    }
+}
+impl fmt::Debug for TokenStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // never print the key
+        f.debug_struct("TokenStore")
+            .field("key_source", &self.key_source)
+            .field("locked", &self.is_locked())
+            .finish()
+    }
+}
+impl TokenStore {
+    pub fn open() -> Self {
+        let file = Self::read_file().unwrap_or_else(|e| {
+            warn!("Failed to read token store: {}", e);
+            None
+        });
+
+        // once the user has picked a passphrase, stick with it
+        if file
+            .as_ref()
+            .is_some_and(|f| f.key_source == KeySource::Passphrase)
+        {
+            return Self::default();
+        }
+
+        match Self::load_keyring_key() {
+            Ok(key) => Self {
+                key_source: KeySource::Keyring,
+                key: Some(key),
+                salt: None,
+            },
+            Err(e) => {
+                warn!(
+                    "OS keyring unavailable ({}); saved logins will need a passphrase",
+                    e
+                );
+                Self::default()
+            }
+        }
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.key.is_none()
+    }
+
+    pub fn has_saved_data(&self) -> bool {
+        Self::get_path().exists()
+    }
+
+    pub fn get_status(&self) -> TokenStoreStatus {
+        TokenStoreStatus {
+            key_source: self.key_source,
+            locked: self.is_locked(),
+        }
+    }
+
+    /// Derives the key from `passphrase`. If there's already a passphrase-protected store
+    /// on disk, the passphrase has to match it; otherwise this sets a new passphrase.
+    pub fn unlock(&mut self, passphrase: &str) -> Result<()> {
+        if self.key_source == KeySource::Keyring {
+            return Ok(());
+        }
+        if passphrase.is_empty() {
+            return Err("Passphrase cannot be empty".into());
+        }
+
+        let existing = Self::read_file()?;
+        if existing
+            .as_ref()
+            .is_some_and(|f| f.key_source == KeySource::Keyring)
+        {
+            // a new passphrase would mean overwriting logins we just can't read right now
+            return Err(KEYRING_FILE_UNREADABLE.into());
+        }
+        let salt = match existing.as_ref().and_then(|f| f.salt.as_deref()) {
+            Some(salt) => decode(salt)?,
+            None => {
+                let mut salt = vec![0; SALT_LEN];
+                OsRng.fill_bytes(&mut salt);
+                salt
+            }
+        };
+
+        let key = derive_key(passphrase, &salt)?;
+        if let Some(file) = &existing {
+            decrypt(&key, file).map_err(|_| Error::InvalidPassphrase)?;
+        }
+
+        self.key = Some(key);
+        self.salt = Some(salt);
+        Ok(())
+    }
+
+    /// Returns the decrypted contents of the store, or None if nothing has been saved yet
+    pub fn read(&self) -> Result<Option<Vec<u8>>> {
+        let Some(file) = Self::read_file()? else {
+            return Ok(None);
+        };
+        let key = self.key.as_ref().ok_or(Error::TokenStoreLocked)?;
+        if file.key_source != self.key_source {
+            return Err("Saved tokens were encrypted with a different key".into());
+        }
+        decrypt(key, &file).map(Some)
+    }
+
+    pub fn write(&self, plaintext: &[u8]) -> Result<()> {
+        let key = self.key.as_ref().ok_or(Error::TokenStoreLocked)?;
+        if self.key_source != KeySource::Keyring
+            && Self::read_file()?.is_some_and(|f| f.key_source == KeySource::Keyring)
+        {
+            return Err(KEYRING_FILE_UNREADABLE.into());
+        }
+        let cipher = ChaCha20Poly1305::new(key);
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| "Failed to encrypt tokens")?;
+
+        let file = StoreFile {
+            key_source: self.key_source,
+            salt: self.salt.as_ref().map(|salt| STANDARD.encode(salt)),
+            nonce: STANDARD.encode(nonce),
+            ciphertext: STANDARD.encode(ciphertext),
+        };
+        let file_str = serde_json::to_string_pretty(&file)?;
+        std::fs::write(Self::get_path(), file_str)?;
+        Ok(())
+    }
+
+    fn get_path() -> PathBuf {
+        get_app_statics().app_data_dir.join(STORE_FILE_NAME)
+    }
+
+    fn read_file() -> Result<Option<StoreFile>> {
+        let path = Self::get_path();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let file_str = std::fs::read_to_string(path)?;
+        Ok(Some(serde_json::from_str(&file_str)?))
+    }
+
+    /// Fetches the store key from the OS keyring, generating one on first use
+    fn load_keyring_key() -> keyring::Result<Key> {
+        let entry = keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER)?;
+        match entry.get_password() {
+            Ok(encoded) => match STANDARD.decode(encoded) {
+                Ok(bytes) if bytes.len() == 32 => Ok(*Key::from_slice(&bytes)),
+                _ => Err(keyring::Error::Invalid(
+                    KEYRING_USER.to_string(),
+                    "not a 32-byte base64 key".to_string(),
+                )),
+            },
+            Err(keyring::Error::NoEntry) => {
+                if Self::get_path().exists() {
+                    warn!("Token store key missing from keyring; saved logins will be lost");
+                }
+                let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+                entry.set_password(&STANDARD.encode(key))?;
+                Ok(key)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Failed to derive key: {}", e))?;
+    Ok(key)
+}
+
+fn decrypt(key: &Key, file: &StoreFile) -> Result<Vec<u8>> {
+    let nonce = decode(&file.nonce)?;
+    if nonce.len() != NONCE_LEN {
+        return Err("Invalid token store nonce".into());
+    }
+    let ciphertext = decode(&file.ciphertext)?;
+    let cipher = ChaCha20Poly1305::new(key);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_ref())
+        .map_err(|_| "Failed to decrypt saved tokens")?;
+    Ok(plaintext)
+}
+
+fn decode(data: &str) -> Result<Vec<u8>> {
+    STANDARD
+        .decode(data)
+        .map_err(|e| format!("Invalid token store data: {}", e).into())
+}
@@ -1,27 +1,42 @@
 #![allow(dead_code)]
 
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     env,
+    net::{IpAddr, Ipv6Addr, SocketAddr},
     path::{Path, PathBuf},
     process::Command,
     sync::{OnceLock, mpsc},
     time::Duration,
 };
 
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
 use dns_lookup::lookup_host;
 use ffbuildtool::{FailReason, ItemProgress, Version};
+use hickory_resolver::{TokioResolver, config::LookupIpStrategy};
 use log::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tauri::Emitter as _;
+use tokio::net::TcpStream;
 use uuid::Uuid;
 
 use crate::{
-    CACHE_PROGRESS_EVENT, CacheEvent, CacheProgress, CacheProgressItem, Result,
+    CACHE_PROGRESS_EVENT, CacheEvent, CacheProgress, CacheProgressItem, Error, Result,
+    config::AddressFamily,
     state::{LaunchProfile, get_app_statics},
 };
 
-static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+const DEFAULT_GAME_PORT: u16 = 23000;
+const SRV_SERVICE: &str = "_openfusion._tcp";
+static DNS_RESOLVER: OnceLock<Option<TokioResolver>> = OnceLock::new();
+
+pub(crate) static APP_USER_AGENT: &str =
+    concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// What the main file is called in offline caches
+pub(crate) const MAIN_FILE_NAME: &str = "main.unity3d";
+
 static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 pub(crate) fn get_http_client() -> &'static reqwest::Client {
     HTTP_CLIENT.get_or_init(|| {
@@ -56,41 +71,190 @@ pub(crate) fn get_timestamp() -> u64 {
     now.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs()
 }
 
-fn split_addr_port(addr_port: &str) -> Result<(String, u16)> {
-    const DEFAULT_PORT: u16 = 23000;
+#[derive(Debug, Deserialize)]
+pub(crate) struct JwtClaims {
+    pub iat: Option<u64>,
+    pub exp: Option<u64>,
+}
+
+/// Reads the claims out of a JWT without verifying it.
+/// Returns None if the token isn't a JWT.
+pub(crate) fn decode_jwt_claims(token: &str) -> Option<JwtClaims> {
+    let mut parts = token.trim().split('.');
+    let (Some(_header), Some(payload), Some(_signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    let payload = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    serde_json::from_slice(&payload).ok()
+}
+
+/// Splits an address into host and port, leaving the port empty if it wasn't given
+fn parse_addr_port(addr_port: &str) -> Result<(String, Option<u16>)> {
+    // bracketed IPv6 literal, e.g. [2001:db8::1]:23000
+    if let Some(rest) = addr_port.strip_prefix('[') {
+        let (addr, rest) = rest
+            .split_once(']')
+            .ok_or(format!("Missing closing bracket in address {}", addr_port))?;
+        let port = if let Some(port) = rest.strip_prefix(':') {
+            Some(port.parse::<u16>()?)
+        } else if rest.is_empty() {
+            None
+        } else {
+            return Err(format!("Invalid address {}", addr_port).into());
+        };
+        return Ok((addr.to_string(), port));
+    }
+
+    // bare IPv6 literal. can't carry a port without brackets
+    if addr_port.parse::<Ipv6Addr>().is_ok() {
+        return Ok((addr_port.to_string(), None));
+    }
+
     let mut parts = addr_port.split(':');
     let addr = parts.next().ok_or("Missing address")?.to_string();
     let port = if let Some(port) = parts.next() {
-        port.parse::<u16>()?
+        Some(port.parse::<u16>()?)
     } else {
-        DEFAULT_PORT
+        None
     };
     Ok((addr, port))
 }
 
-fn resolve_host(host: &str) -> Result<String> {
-    let addrs = lookup_host(host)?;
-    for addr in addrs {
-        if let std::net::IpAddr::V4(addr) = addr {
-            return Ok(addr.to_string());
+fn split_addr_port(addr_port: &str) -> Result<(String, u16)> {
+    let (addr, port) = parse_addr_port(addr_port)?;
+    Ok((addr, port.unwrap_or(DEFAULT_GAME_PORT)))
+}
+
+/// Joins a host and port, bracketing IPv6 addresses
+fn join_addr_port(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+async fn resolve_host(host: &str, family: AddressFamily) -> Result<IpAddr> {
+    let addrs: Vec<IpAddr> = match get_dns_resolver() {
+        Some(resolver) => resolver
+            .lookup_ip(host)
+            .await
+            .map_err(|e| format!("Failed to resolve {}: {}", host, e))?
+            .iter()
+            .collect(),
+        None => {
+            // the system resolver blocks, so keep it off the async runtime
+            let owned_host = host.to_string();
+            tauri::async_runtime::spawn_blocking(move || lookup_host(&owned_host))
+                .await
+                .map_err(|e| e.to_string())??
         }
+    };
+    let v4 = addrs.iter().find(|addr| addr.is_ipv4());
+    let v6 = addrs.iter().find(|addr| addr.is_ipv6());
+    let picked = match family {
+        AddressFamily::PreferIpv4 => v4.or(v6),
+        AddressFamily::PreferIpv6 => v6.or(v4),
+        AddressFamily::Ipv4Only => v4,
+        AddressFamily::Ipv6Only => v6,
+    };
+    picked
+        .copied()
+        .ok_or(format!("No address found for {} matching {:?}", host, family).into())
+}
+
+fn get_dns_resolver() -> Option<&'static TokioResolver> {
+    DNS_RESOLVER
+        .get_or_init(|| match TokioResolver::builder_tokio() {
+            Ok(mut builder) => {
+                // the address family preference picks between them, so get both
+                builder.options_mut().ip_strategy = LookupIpStrategy::Ipv4AndIpv6;
+                Some(builder.build())
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to set up DNS resolver; falling back to the system one without SRV lookups: {}",
+                    e
+                );
+                None
+            }
+        })
+        .as_ref()
+}
+
+/// Looks up the `_openfusion._tcp` SRV record for a domain, returning the target host and port
+async fn lookup_srv(domain: &str) -> Option<(String, u16)> {
+    let resolver = get_dns_resolver()?;
+    let name = format!("{}.{}.", SRV_SERVICE, domain.trim_end_matches('.'));
+    let lookup = match resolver.srv_lookup(name).await {
+        Ok(lookup) => lookup,
+        Err(e) => {
+            debug!("No SRV record for {}: {}", domain, e);
+            return None;
+        }
+    };
+
+    // lowest priority wins; heavier records win ties
+    let srv = lookup
+        .iter()
+        .min_by_key(|srv| (srv.priority(), std::cmp::Reverse(srv.weight())))?;
+    let target = srv.target().to_utf8();
+    let target = target.trim_end_matches('.');
+    // a target of "." means the service is explicitly unavailable at this domain
+    if target.is_empty() {
+        return None;
     }
-    Err(format!("No IPv4 address found for {}", host).into())
+    Some((target.to_string(), srv.port()))
 }
 
-pub(crate) fn resolve_server_addr(addr: &str) -> Result<String> {
-    let (host, port) = split_addr_port(addr)?;
+/// Resolves a game server address to `ip:port`, in the `[ip]:port` form for IPv6.
+/// A bare domain (no port) is first checked for an `_openfusion._tcp` SRV record
+/// so operators can move servers around without everyone editing their server list.
+pub(crate) async fn resolve_server_addr(addr: &str, family: AddressFamily) -> Result<String> {
+    let (mut host, port) = parse_addr_port(addr)?;
 
     // if we alredy have an IP, nothing to resolve
-    if host.parse::<std::net::IpAddr>().is_ok() {
-        return Ok(addr.to_string());
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return Ok(SocketAddr::new(ip, port.unwrap_or(DEFAULT_GAME_PORT)).to_string());
     }
 
-    let Ok(ip) = resolve_host(&host) else {
+    let port = match port {
+        Some(port) => port,
+        None => match lookup_srv(&host).await {
+            Some((target, srv_port)) => {
+                debug!("SRV record for {} points to {}:{}", host, target, srv_port);
+                host = target;
+                srv_port
+            }
+            None => DEFAULT_GAME_PORT,
+        },
+    };
+
+    let Ok(ip) = resolve_host(&host, family).await else {
         return Err(format!("Failed to resolve game server address {}", addr).into());
     };
     debug!("Resolved {} to {}", host, ip);
-    Ok(format!("{}:{}", ip, port))
+    Ok(SocketAddr::new(ip, port).to_string())
+}
+
+/// Opens (and immediately drops) a TCP connection to a game server address,
+/// returning how long the connection took. Uses the default port if none is given.
+pub(crate) async fn probe_game_addr(addr: &str) -> Result<Duration> {
+    const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+    let (host, port) = split_addr_port(addr)?;
+    let unreachable = |reason: String| Error::LoginServerUnreachable {
+        addr: join_addr_port(&host, port),
+        reason,
+    };
+
+    let start = std::time::Instant::now();
+    match tokio::time::timeout(PROBE_TIMEOUT, TcpStream::connect((host.as_str(), port))).await {
+        Ok(Ok(_)) => Ok(start.elapsed()),
+        Ok(Err(e)) => Err(unreachable(e.to_string())),
+        Err(_) => Err(unreachable("timed out".to_string())),
+    }
 }
 
 pub(crate) fn get_default_cache_dir() -> String {
@@ -248,6 +412,17 @@ pub(crate) fn get_dir_size(dir: &PathBuf) -> Result<u64> {
     Ok(size)
 }
 
+/// Like `get_dir_size`, but a missing or unreadable directory counts as empty
+pub(crate) fn get_dir_size_or_zero(dir: &PathBuf) -> u64 {
+    if !dir.exists() {
+        return 0;
+    }
+    get_dir_size(dir).unwrap_or_else(|e| {
+        warn!("Failed to get size of {}: {}", dir.to_string_lossy(), e);
+        0
+    })
+}
+
 pub(crate) fn copy_dir(src: &PathBuf, dest: &PathBuf) -> Result<()> {
     if !dest.exists() {
         std::fs::create_dir_all(dest)?;
@@ -301,6 +476,173 @@ pub(crate) fn get_version_name(version: &Version) -> String {
     }
 }
 
+/// Totals the compressed and uncompressed sizes of a version's bundles,
+/// if its manifest lists them. Barebones versions don't.
+pub(crate) fn get_manifest_sizes(version: &Version) -> (Option<u64>, Option<u64>) {
+    let files = get_manifest_file_infos(version);
+    let bundles: Vec<&ManifestFileInfo> = files
+        .iter()
+        .filter(|(name, _)| *name != MAIN_FILE_NAME)
+        .map(|(_, info)| info)
+        .collect();
+    if bundles.is_empty() {
+        return (None, None);
+    }
+    (
+        Some(bundles.iter().map(|info| info.size).sum()),
+        bundles.iter().map(|info| info.uncompressed_size).sum(),
+    )
+}
+
+/// Size and SHA-256 of a compressed file, as listed in a version's manifest
+#[derive(Debug, Clone)]
+pub(crate) struct ManifestFileInfo {
+    pub hash: String,
+    pub size: u64,
+    /// Total size of what a bundle unpacks to. None for the main file.
+    pub uncompressed_size: Option<u64>,
+}
+
+/// Lists the compressed files in a version's manifest by their name in the offline cache,
+/// including the main file. Barebones versions don't list any.
+pub(crate) fn get_manifest_file_infos(version: &Version) -> HashMap<String, ManifestFileInfo> {
+    fn parse(info: &Value) -> Option<ManifestFileInfo> {
+        Some(ManifestFileInfo {
+            hash: info["hash"].as_str()?.to_lowercase(),
+            size: info["size"].as_u64()?,
+            uncompressed_size: None,
+        })
+    }
+
+    let mut files = HashMap::new();
+    let Ok(manifest) = serde_json::to_value(version) else {
+        return files;
+    };
+    if let Some(info) = parse(&manifest["main_file_info"]) {
+        files.insert(MAIN_FILE_NAME.to_string(), info);
+    }
+    if let Some(bundles) = manifest["bundles"].as_object() {
+        for (name, bundle) in bundles {
+            if let Some(mut info) = parse(&bundle["compressed_info"]) {
+                info.uncompressed_size = bundle["uncompressed_info"]
+                    .as_object()
+                    .and_then(|files| files.values().map(|f| f["size"].as_u64()).sum());
+                files.insert(name.clone(), info);
+            }
+        }
+    }
+    files
+}
+
+/// Whether an offline cache has every file its manifest lists, at the right size.
+/// Doesn't check hashes, so it's cheap enough to run before every launch.
+pub(crate) fn is_offline_cache_complete(version: &Version, dir: &Path) -> bool {
+    let files = get_manifest_file_infos(version);
+    if files.is_empty() {
+        // nothing to go on, so trust whatever is there
+        return dir.exists();
+    }
+    files
+        .keys()
+        .all(|name| is_offline_file_complete(&files, dir, name))
+}
+
+/// Whether an offline cache has one of its manifest's files at the right size.
+/// Files the manifest doesn't list only have to be there.
+pub(crate) fn is_offline_file_complete(
+    files: &HashMap<String, ManifestFileInfo>,
+    dir: &Path,
+    name: &str,
+) -> bool {
+    let meta = std::fs::metadata(dir.join(name));
+    match files.get(name) {
+        Some(info) => meta.is_ok_and(|meta| meta.len() == info.size),
+        None => meta.is_ok(),
+    }
+}
+
+/// Returns a copy of a version that fetches its assets, main file included, from `asset_url` instead
+pub(crate) fn with_asset_url(version: &Version, asset_url: &str) -> Result<Version> {
+    let mut manifest = serde_json::to_value(version)?;
+    let Some(url) = manifest.get_mut("asset_url") else {
+        return Err("Version manifest has no asset URL".into());
+    };
+    *url = Value::String(asset_url.to_string());
+    if let Some(main_url) = manifest.get_mut("main_file_url")
+        && main_url.is_string()
+    {
+        *main_url = Value::String(format!("{}/{}", asset_url, MAIN_FILE_NAME));
+    }
+    Ok(serde_json::from_value(manifest)?)
+}
+
+/// Lists the asset bundles in a version's manifest. Barebones versions don't list any.
+pub(crate) fn get_manifest_bundle_names(version: &Version) -> Vec<String> {
+    let Ok(manifest) = serde_json::to_value(version) else {
+        return Vec::new();
+    };
+    manifest["bundles"]
+        .as_object()
+        .map(|bundles| bundles.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns a copy of `version` whose manifest only lists the given bundles,
+/// so validating it only looks at those
+pub(crate) fn with_only_bundles(version: &Version, bundle_names: &[String]) -> Result<Version> {
+    let mut manifest = serde_json::to_value(version)?;
+    if let Some(bundles) = manifest.get_mut("bundles").and_then(Value::as_object_mut) {
+        bundles.retain(|name, _| bundle_names.contains(name));
+    }
+    Ok(serde_json::from_value(manifest)?)
+}
+
+/// Lists the manifest fields that differ between two copies of a version, as dotted paths
+/// (e.g. `asset_url` or `bundles.Map_01.unity3d`). Empty if the manifests are identical.
+pub(crate) fn diff_versions(old: &Version, new: &Version) -> Result<Vec<String>> {
+    const MAX_DEPTH: usize = 2;
+
+    fn diff(old: &Value, new: &Value, path: &str, depth: usize, out: &mut Vec<String>) {
+        match (old, new) {
+            (Value::Object(old), Value::Object(new)) if depth < MAX_DEPTH => {
+                let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+                for key in keys {
+                    let key_path = match path {
+                        "" => key.clone(),
+                        _ => format!("{}.{}", path, key),
+                    };
+                    match (old.get(key), new.get(key)) {
+                        (Some(o), Some(n)) => diff(o, n, &key_path, depth + 1, out),
+                        (None, Some(_)) => out.push(format!("{} (added)", key_path)),
+                        (Some(_), None) => out.push(format!("{} (removed)", key_path)),
+                        (None, None) => unreachable!(),
+                    }
+                }
+            }
+            _ if old != new => out.push(path.to_string()),
+            _ => {}
+        }
+    }
+
+    let mut changes = Vec::new();
+    diff(
+        &serde_json::to_value(old)?,
+        &serde_json::to_value(new)?,
+        "",
+        0,
+        &mut changes,
+    );
+    Ok(changes)
+}
+
+/// Whether any of the changes from `diff_versions` are to the version's files,
+/// which leaves caches made from the old manifest unchecked against the new one
+pub(crate) fn do_changes_touch_files(changes: &[String]) -> bool {
+    changes
+        .iter()
+        .any(|c| c.starts_with("bundles") || c.starts_with("main_file_info"))
+}
+
 pub(crate) fn import_versions(to_import: Vec<Version>) -> Result<Vec<Version>> {
     let versions_path = get_app_statics().app_data_dir.join("versions");
     if !versions_path.exists() {
@@ -355,16 +697,20 @@ pub(crate) async fn do_simple_get(url: &str) -> Result<String> {
     Ok(text)
 }
 
+/// Forwards cache progress to the frontend until the operation is done. Items in `items` are
+/// reported from the start and updated as their files get processed again.
+/// Returns everything that was reported, or None if the operation was cancelled.
 pub(crate) fn cache_progress_loop(
     offline: bool,
     app_handle: tauri::AppHandle,
     item_rx: mpsc::Receiver<CacheEvent>,
     uuid: Uuid,
-) {
+    mut items: HashMap<String, CacheProgressItem>,
+) -> Option<HashMap<String, CacheProgressItem>> {
     const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(250);
 
-    let mut items = HashMap::new();
     let mut done = false;
+    let mut cancelled = false;
     while !done {
         std::thread::sleep(POLL_INTERVAL);
         while let Ok(event) = item_rx.try_recv() {
@@ -375,6 +721,10 @@ pub(crate) fn cache_progress_loop(
                 CacheEvent::Done => {
                     done = true;
                 }
+                CacheEvent::Cancelled => {
+                    done = true;
+                    cancelled = true;
+                }
             }
         }
 
@@ -383,11 +733,13 @@ pub(crate) fn cache_progress_loop(
             uuid,
             items: items.clone(),
             done,
+            cancelled,
         };
         if let Err(e) = app_handle.emit(CACHE_PROGRESS_EVENT, progress) {
             error!("Failed to emit cache progress event: {}", e);
         }
     }
+    (!cancelled).then_some(items)
 }
 
 pub(crate) fn cache_progress_callback(
@@ -440,6 +792,30 @@ pub(crate) fn send_alert(app_handle: tauri::AppHandle, variant: AlertVariant, me
     }
 }
 
+/// Where user-facing alerts go. The GUI emits them to the frontend,
+/// while headless mode has no webview and just prints them to stderr.
+#[derive(Clone)]
+pub(crate) enum AlertSink {
+    App(tauri::AppHandle),
+    Stderr,
+}
+impl AlertSink {
+    pub(crate) fn send(&self, variant: AlertVariant, message: &str) {
+        match self {
+            AlertSink::App(app_handle) => send_alert(app_handle.clone(), variant, message),
+            AlertSink::Stderr => {
+                let label = match variant {
+                    AlertVariant::Info => "info",
+                    AlertVariant::Warning => "warning",
+                    AlertVariant::Error => "error",
+                    AlertVariant::Success => "success",
+                };
+                eprintln!("[{}] {}", label, message);
+            }
+        }
+    }
+}
+
 fn tokenize_launch_command(launch_cmd: &str) -> Vec<String> {
     let mut tokens = Vec::new();
     let mut current_token = String::new();
@@ -555,13 +931,9 @@ pub(crate) fn log_command(command: &Command) {
     debug!("Launching game: {}", command_str);
 }
 
-pub(crate) async fn does_web_file_exist(url: &str) -> bool {
+/// Asks for the headers of a web file, so callers can tell a missing file from a failing host
+pub(crate) async fn get_web_file_status(url: &str) -> reqwest::Result<reqwest::StatusCode> {
     let client = get_http_client();
-    match client.head(url).send().await {
-        Ok(response) => response.status().is_success(),
-        Err(e) => {
-            debug!("Failed to check if web file exists: {}", e);
-            false
-        }
-    }
+    let response = client.head(url).send().await?;
+    Ok(response.status())
 }
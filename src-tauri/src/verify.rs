@@ -0,0 +1,218 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    time::Duration,
+};
+
+use ffbuildtool::{ItemProgress, Version};
+use log::*;
+use tauri::Manager as _;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::{
+    CacheEvent, CacheProgressItem, VALIDATION_SEMAPHORE, cache, launch, record_validation_report,
+    state::AppState,
+    util::{self, AlertVariant},
+};
+
+// how often to look for caches that are due
+const WAKE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+// how often a running verification checks that the launcher is still idle
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+static VERIFY_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// A cache whose last validation is older than the verification interval
+struct DueCache {
+    version: Version,
+    offline: bool,
+    dir: PathBuf,
+    was_bad: bool,
+}
+
+/// Spawns the background task that re-validates installed caches when they're due.
+/// Only the first call does anything. The setting is checked every time the task wakes up,
+/// so turning it on or off takes effect without a restart.
+pub(crate) fn start_verifying(app_handle: tauri::AppHandle) {
+    if VERIFY_STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(WAKE_INTERVAL).await;
+            verify_due(&app_handle).await;
+        }
+    });
+}
+
+/// Validates each installed cache that is due, one at a time, for as long as the launcher is idle.
+/// Whatever doesn't get done waits for the next wake-up.
+pub(crate) async fn verify_due(app_handle: &tauri::AppHandle) {
+    let due = get_due_caches(app_handle).await;
+    for cache in due {
+        if !is_idle(app_handle).await || cache::is_any_op_running().await {
+            debug!("Launcher is busy; putting off background cache verification");
+            return;
+        }
+        verify_one(app_handle, cache).await;
+    }
+}
+
+async fn get_due_caches(app_handle: &tauri::AppHandle) -> Vec<DueCache> {
+    let state = app_handle.state::<Mutex<AppState>>();
+    let state = state.lock().await;
+    let settings = &state.config.launcher;
+    if !settings.background_cache_verification {
+        return Vec::new();
+    }
+
+    let interval = settings.cache_verification_interval_hours.max(1) * 60 * 60;
+    let now = util::get_timestamp();
+    let mut due = Vec::new();
+    for version in state.versions.get_entries() {
+        for offline in [false, true] {
+            let base_cache_dir = if offline {
+                &settings.offline_cache_path
+            } else {
+                &settings.game_cache_path
+            };
+            let dir = util::get_cache_dir_for_version(base_cache_dir, version);
+            if util::is_dir_empty(&dir).unwrap_or(true) {
+                // not installed
+                continue;
+            }
+            let report = state.validation_reports.get(version.get_uuid(), offline);
+            if report.is_some_and(|r| now.saturating_sub(r.get_last_validated()) < interval) {
+                continue;
+            }
+            due.push(DueCache {
+                version: version.clone(),
+                offline,
+                dir,
+                was_bad: report.is_some_and(|r| count_bad(offline, r.get_items()) > 0),
+            });
+        }
+    }
+    due
+}
+
+/// Not launching or playing. Cache operations are checked separately,
+/// since the verification holds one of its own while it runs.
+async fn is_idle(app_handle: &tauri::AppHandle) -> bool {
+    if launch::GAME_RUNNING.load(Ordering::SeqCst) {
+        return false;
+    }
+    let state = app_handle.state::<Mutex<AppState>>();
+    state.lock().await.launch_cmd.is_none()
+}
+
+async fn wait_until_busy(app_handle: &tauri::AppHandle) {
+    loop {
+        tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+        if !is_idle(app_handle).await {
+            return;
+        }
+    }
+}
+
+/// Game caches fill up as the game plays, so only files that are there but wrong count against
+/// them. Offline caches are supposed to be complete.
+fn count_bad(offline: bool, items: &HashMap<String, CacheProgressItem>) -> usize {
+    items
+        .values()
+        .filter(|item| item.corrupt && (offline || !item.missing))
+        .count()
+}
+
+async fn verify_one(app_handle: &tauri::AppHandle, cache: DueCache) {
+    let uuid = cache.version.get_uuid();
+    let offline = cache.offline;
+    let Ok(_permit) = VALIDATION_SEMAPHORE.acquire().await else {
+        return;
+    };
+    let Ok(token) = cache::begin_op(uuid, offline).await else {
+        // somebody got to it first
+        return;
+    };
+    let version_name = util::get_version_name(&cache.version);
+    debug!(
+        "Verifying {} cache for {} in the background",
+        if offline { "offline" } else { "game" },
+        version_name
+    );
+
+    let (tx, rx) = mpsc::channel();
+    let tx_clone = tx.clone();
+    let cb = move |_version_uuid: &Uuid, item_name: &str, progress: ItemProgress| {
+        util::cache_progress_callback(tx_clone.clone(), item_name, progress);
+    };
+    let cb = Arc::new(cb);
+
+    // the frontend shows this like any other validation, stop button included
+    let progress_handle = app_handle.clone();
+    let progress = tauri::async_runtime::spawn_blocking(move || {
+        let items =
+            util::cache_progress_loop(offline, progress_handle.clone(), rx, uuid, HashMap::new());
+        record_validation_report(&progress_handle, uuid, offline, items.clone());
+        items
+    });
+
+    let path = cache.dir.to_string_lossy().to_string();
+    let validate = async {
+        if offline {
+            cache.version.validate_compressed(&path, Some(cb)).await
+        } else {
+            cache.version.validate_uncompressed(&path, Some(cb)).await
+        }
+    };
+    let cancelled = tokio::select! {
+        _ = token.cancelled() => true,
+        _ = wait_until_busy(app_handle) => {
+            info!("Launcher is busy; stopping background cache verification");
+            true
+        }
+        _ = validate => false,
+    };
+    cache::end_op(uuid, offline).await;
+
+    let event = if cancelled {
+        CacheEvent::Cancelled
+    } else {
+        CacheEvent::Done
+    };
+    if let Err(e) = tx.send(event) {
+        error!("Failed to send cache done event: {}", e);
+    }
+
+    let items = match progress.await {
+        Ok(Some(items)) => items,
+        Ok(None) => return,
+        Err(e) => {
+            error!("Background cache verification failed: {}", e);
+            return;
+        }
+    };
+    let bad = count_bad(offline, &items);
+    if bad == 0 {
+        debug!("{} checks out", version_name);
+    } else if !cache.was_bad {
+        // only when it goes bad, so a cache nobody repairs doesn't nag every interval
+        let (kind, problem) = if offline {
+            ("offline", "corrupt or missing")
+        } else {
+            ("game", "corrupt")
+        };
+        let msg = format!(
+            "The {} cache for {} has {} {} file(s). Repair it in Settings > Game Builds before your next launch.",
+            kind, version_name, bad, problem
+        );
+        warn!("{}", msg);
+        util::send_alert(app_handle.clone(), AlertVariant::Warning, &msg);
+    }
+}